@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate bencher;
+extern crate hal;
+
+use bencher::Bencher;
+use hal::link::Link;
+use hal::resource::Resource;
+
+/// Builds a tree of resources five levels deep, six wide at each level,
+/// either handing every link, string and child over by value or by
+/// reference, which clones it
+fn tree(depth: usize, owned: bool) -> Resource {
+    let mut hal = Resource::new();
+    let href = format!("/nodes/{}", depth);
+    let name = format!("node at depth {}", depth);
+    if owned {
+        hal.add_link_owned("self", Link::new(href)).add_state("name", name);
+    } else {
+        hal.add_link("self", &Link::new(href)).add_state("name", name.clone());
+    }
+
+    if depth > 0 {
+        for _ in 0..6 {
+            let child = tree(depth - 1, owned);
+            if owned {
+                hal.add_resource_owned("item", child);
+            } else {
+                hal.add_resource("item", &child);
+            }
+        }
+    }
+
+    hal
+}
+
+fn build_cloned(b: &mut Bencher) {
+    b.iter(|| tree(5, false));
+}
+
+fn build_owned(b: &mut Bencher) {
+    b.iter(|| tree(5, true));
+}
+
+benchmark_group!(benches, build_cloned, build_owned);
+benchmark_main!(benches);
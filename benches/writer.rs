@@ -0,0 +1,64 @@
+#[macro_use]
+extern crate bencher;
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use bencher::Bencher;
+use hal::link::Link;
+use hal::resource::Resource;
+use hal::writer;
+use serialize::json::ToJson;
+
+/// A page of orders with a few thousand embedded resources
+fn orders() -> Resource {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("next", &Link::new("/orders?page=2"))
+        .add_link("ea:find", &Link::new("/orders{?id}"))
+        .add_state("currentlyProcessing", 14i64)
+        .add_state("shippedToday", 20i64);
+
+    for id in 0..2000 {
+        let mut order = Resource::with_self(format!("/orders/{}", id));
+        order.add_link("ea:basket", &Link::new(format!("/baskets/{}", id)))
+            .add_link("ea:customer", &Link::new(format!("/customers/{}", id % 97)))
+            .add_state("total", id as f64 * 1.5)
+            .add_state("currency", "USD")
+            .add_state("status", "processing")
+            .add_state("items", vec!["widget", "gadget", "gizmo"]);
+        hal.add_resource("ea:order", &order);
+    }
+
+    hal
+}
+
+fn to_json_to_string(b: &mut Bencher) {
+    let hal = orders();
+    b.iter(|| hal.to_json().to_string());
+}
+
+fn to_writer(b: &mut Bencher) {
+    let hal = orders();
+    let mut out = Vec::new();
+    b.iter(|| {
+        out.clear();
+        writer::to_writer(&mut out, &hal).unwrap();
+    });
+}
+
+fn to_json_pretty(b: &mut Bencher) {
+    let hal = orders();
+    b.iter(|| hal.to_json().pretty().to_string());
+}
+
+fn to_writer_pretty(b: &mut Bencher) {
+    let hal = orders();
+    let mut out = Vec::new();
+    b.iter(|| {
+        out.clear();
+        writer::to_writer_pretty(&mut out, &hal).unwrap();
+    });
+}
+
+benchmark_group!(benches, to_json_to_string, to_writer, to_json_pretty, to_writer_pretty);
+benchmark_main!(benches);
@@ -0,0 +1,138 @@
+use syn::{Attribute, Data, DeriveInput, Error, Fields, GenericArgument, Ident, LitStr,
+          PathArguments, Result, Type};
+
+/// Mirrors `hal::resource::RESERVED_KEYS`
+const RESERVED_KEYS: &[&str] = &["_links", "_embedded"];
+
+/// How a struct field maps onto a Hal resource
+pub enum Role {
+    /// A state member with the given key
+    State(String),
+    /// One or more links for the given rel
+    Link(String),
+    /// One or more embedded resources for the given rel
+    Embed(String),
+    /// Not part of the resource
+    Skip,
+}
+
+/// The container type wrapping a link or embedded field
+pub enum Arity {
+    One,
+    Optional,
+    Many,
+}
+
+pub struct Field<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    pub role: Role,
+}
+
+/// Reads the `#[hal(self = "...")]` template from the struct attributes
+pub fn self_template(input: &DeriveInput) -> Result<Option<LitStr>> {
+    let mut template = None;
+
+    for attr in hal_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("self") {
+                template = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported hal attribute, expected `self`"))
+            }
+        })?;
+    }
+
+    Ok(template)
+}
+
+/// Reads the fields of a struct with named fields along with their role
+pub fn fields(input: &DeriveInput) -> Result<Vec<Field<'_>>> {
+    let named = match input.data {
+        Data::Struct(ref data) => {
+            match data.fields {
+                Fields::Named(ref fields) => &fields.named,
+                _ => return Err(Error::new_spanned(input, "expected a struct with named fields")),
+            }
+        }
+        _ => return Err(Error::new_spanned(input, "expected a struct with named fields")),
+    };
+
+    let mut fields = Vec::new();
+    for field in named {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut role = Role::State(ident.to_string());
+
+        for attr in hal_attrs(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    role = Role::Skip;
+                } else if meta.path.is_ident("rename") {
+                    let name: LitStr = meta.value()?.parse()?;
+                    role = Role::State(name.value());
+                } else if meta.path.is_ident("link") {
+                    let rel: LitStr = meta.value()?.parse()?;
+                    role = Role::Link(rel.value());
+                } else if meta.path.is_ident("embed") {
+                    let rel: LitStr = meta.value()?.parse()?;
+                    role = Role::Embed(rel.value());
+                } else {
+                    return Err(meta.error("unsupported hal attribute, expected one of \
+                                           `skip`, `rename`, `link` or `embed`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if let Role::State(ref key) = role {
+            if RESERVED_KEYS.contains(&&key[..]) {
+                let msg = format!("`{}` is reserved by Hal and cannot be used as a state key", key);
+                return Err(Error::new_spanned(field, msg));
+            }
+        }
+
+        fields.push(Field {
+            ident,
+            ty: &field.ty,
+            role,
+        });
+    }
+
+    Ok(fields)
+}
+
+/// Detects an `Option<T>` or `Vec<T>` field, returning the element type
+pub fn arity(ty: &Type) -> (Arity, &Type) {
+    if let Type::Path(ref path) = *ty {
+        if let Some(segment) = path.path.segments.last() {
+            if let PathArguments::AngleBracketed(ref args) = segment.arguments {
+                if let Some(GenericArgument::Type(inner)) = args.args.first() {
+                    if segment.ident == "Option" {
+                        return (Arity::Optional, inner);
+                    }
+                    if segment.ident == "Vec" {
+                        return (Arity::Many, inner);
+                    }
+                }
+            }
+        }
+    }
+
+    (Arity::One, ty)
+}
+
+/// Whether a field type is `Link`, however its path is written
+pub fn is_link(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref path) => match path.path.segments.last() {
+            Some(segment) => segment.ident == "Link",
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+fn hal_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("hal"))
+}
@@ -0,0 +1,106 @@
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Result};
+
+use attr::{self, Arity, Role};
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let fields = attr::fields(input)?;
+    // The self template only applies to `ToHal`, but is still checked here
+    attr::self_template(input)?;
+
+    let members = fields.iter().map(|field| {
+        let ident = field.ident;
+        let ty = field.ty;
+        let (arity, inner) = attr::arity(ty);
+
+        let value = match field.role {
+            Role::State(ref key) => {
+                quote! { resource.get_state_as::<#ty>(#key)? }
+            }
+            Role::Link(ref rel) => {
+                // A `Link` field reads the whole link, anything else its href
+                let from = if attr::is_link(inner) {
+                    quote! { |link: &::hal::link::Link| link.clone() }
+                } else {
+                    quote! {
+                        |link: &::hal::link::Link| {
+                            <#inner as ::std::convert::From<String>>::from(link.get_href().to_string())
+                        }
+                    }
+                };
+                match arity {
+                    Arity::One => {
+                        quote! {
+                            match resource.get_links(#rel).first() {
+                                Some(link) => (#from)(link),
+                                None => return Err(::hal::error::HalError::MissingField {
+                                    path: "/_links".to_string(),
+                                    field: #rel.to_string(),
+                                }),
+                            }
+                        }
+                    }
+                    Arity::Optional => quote! { resource.get_links(#rel).first().map(#from) },
+                    Arity::Many => quote! { resource.get_links(#rel).iter().map(#from).collect() },
+                }
+            }
+            Role::Embed(ref rel) => {
+                let path = format!("/_embedded/{}", rel.replace('~', "~0").replace('/', "~1"));
+                let from = quote! {
+                    |(i, embedded)| {
+                        <#inner as ::hal::FromHal>::from_hal(embedded)
+                            .map_err(|e| e.nested_in(&format!("{}/{}", #path, i)))
+                    }
+                };
+                match arity {
+                    Arity::One => {
+                        quote! {
+                            match resource.get_embedded(#rel).iter().enumerate().next() {
+                                Some(embedded) => (#from)(embedded)?,
+                                None => return Err(::hal::error::HalError::MissingField {
+                                    path: "/_embedded".to_string(),
+                                    field: #rel.to_string(),
+                                }),
+                            }
+                        }
+                    }
+                    Arity::Optional => {
+                        quote! {
+                            match resource.get_embedded(#rel).iter().enumerate().next() {
+                                Some(embedded) => Some((#from)(embedded)?),
+                                None => None,
+                            }
+                        }
+                    }
+                    Arity::Many => {
+                        quote! {
+                            resource.get_embedded(#rel)
+                                .iter()
+                                .enumerate()
+                                .map(#from)
+                                .collect::<::std::result::Result<_, _>>()?
+                        }
+                    }
+                }
+            }
+            Role::Skip => quote! { ::std::default::Default::default() },
+        };
+
+        quote! { #ident: #value }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::hal::FromHal for #name #ty_generics #where_clause {
+            fn from_hal(resource: &::hal::resource::Resource)
+                -> ::std::result::Result<Self, ::hal::error::HalError>
+            {
+                Ok(#name {
+                    #(#members),*
+                })
+            }
+        }
+    })
+}
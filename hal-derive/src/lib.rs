@@ -0,0 +1,71 @@
+//! Derive macros for the `hal` crate.
+//!
+//! `#[derive(ToHal)]` builds a `Resource` from a struct with named fields, and
+//! `#[derive(FromHal)]` reads the struct back out of a `Resource`:
+//!
+//! ```ignore
+//! #[derive(ToHal)]
+//! #[hal(self = "/orders/{id}")]
+//! struct Order {
+//!     #[hal(skip)]
+//!     id: u64,
+//!     total: f64,
+//!     #[hal(rename = "currencyCode")]
+//!     currency: String,
+//!     #[hal(link = "ea:customer")]
+//!     customer: String,
+//!     #[hal(embed = "ea:item")]
+//!     items: Vec<Item>,
+//! }
+//! ```
+//!
+//! * `self` on the struct adds a `self` link; `{field}` is replaced with the
+//!   `Display` output of that field and `{{`/`}}` are literal braces.
+//! * `link = "rel"` adds the field as a link. The field must convert into a
+//!   `Link`, such as a `String` href or a `Link` itself.
+//! * `embed = "rel"` embeds the field, which must implement `ToHal`.
+//! * `rename = "key"` changes the state key, and `skip` leaves the field out.
+//!
+//! Link and embed fields may be wrapped in `Option` or `Vec`. Every other
+//! field is added as state and must implement `ToHalState`.
+//!
+//! `FromHal` uses the same attributes in reverse. State fields must implement
+//! `FromHalState`, link fields must be a `Link` or convert from the `String`
+//! href, embedded fields must implement `FromHal`, and skipped fields are
+//! filled in with `Default::default()`. The self template is not read back.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+mod attr;
+mod from_hal;
+mod to_hal;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, Error};
+
+#[proc_macro_derive(ToHal, attributes(hal))]
+pub fn derive_to_hal(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    to_hal::expand(&input).unwrap_or_else(compile_error).into()
+}
+
+#[proc_macro_derive(FromHal, attributes(hal))]
+pub fn derive_from_hal(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    from_hal::expand(&input).unwrap_or_else(compile_error).into()
+}
+
+/// Reports errors with an unqualified `compile_error!`, which unlike
+/// `Error::to_compile_error` also resolves in 2015 edition crates
+fn compile_error(error: Error) -> proc_macro2::TokenStream {
+    error.into_iter()
+        .map(|error| {
+            let msg = error.to_string();
+            quote_spanned!(error.span()=> compile_error!(#msg);)
+        })
+        .collect()
+}
@@ -0,0 +1,99 @@
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Error, Ident, LitStr, Result};
+
+use attr::{self, Arity, Role};
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let fields = attr::fields(input)?;
+    let field_names: Vec<&Ident> = fields.iter().map(|field| field.ident).collect();
+
+    let resource = match attr::self_template(input)? {
+        Some(template) => {
+            let (format, args) = self_href(&template, &field_names)?;
+            quote! {
+                ::hal::resource::Resource::with_self(format!(#format, #(self.#args),*))
+            }
+        }
+        None => quote! { ::hal::resource::Resource::new() },
+    };
+
+    let members = fields.iter().map(|field| {
+        let ident = field.ident;
+        match field.role {
+            Role::State(ref key) => {
+                quote! { resource.add_state(#key, self.#ident); }
+            }
+            Role::Link(ref rel) => {
+                let add = quote! { resource.add_link_owned(#rel, ::hal::link::Link::from(value)); };
+                for_each(field.ty, ident, add)
+            }
+            Role::Embed(ref rel) => {
+                let add = quote! {
+                    resource.add_resource_owned(#rel, ::hal::ToHal::to_hal(value));
+                };
+                for_each(field.ty, ident, add)
+            }
+            Role::Skip => quote! {},
+        }
+    });
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::hal::ToHal for #name #ty_generics #where_clause {
+            fn to_hal(self) -> ::hal::resource::Resource {
+                let mut resource = #resource;
+                #(#members)*
+                resource
+            }
+        }
+    })
+}
+
+/// Runs `add` with `value` bound to each element of an `Option` or `Vec`
+/// field, or to the field itself
+fn for_each(ty: &syn::Type, ident: &Ident, add: TokenStream) -> TokenStream {
+    match attr::arity(ty).0 {
+        Arity::One => quote! { { let value = self.#ident; #add } },
+        Arity::Optional => quote! { if let Some(value) = self.#ident { #add } },
+        Arity::Many => quote! { for value in self.#ident { #add } },
+    }
+}
+
+/// Turns a `/orders/{id}` template into a format string and the fields it
+/// uses; `{{` and `}}` stand for literal braces
+fn self_href(template: &LitStr, fields: &[&Ident]) -> Result<(String, Vec<Ident>)> {
+    let value = template.value();
+    let mut format = String::new();
+    let mut args = Vec::new();
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                format.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                format.push_str("}}");
+            }
+            '{' => {
+                let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                match fields.iter().find(|&&field| *field == name) {
+                    Some(field) => args.push((*field).clone()),
+                    None => {
+                        let msg = format!("`{}` is not a field of this struct", name);
+                        return Err(Error::new_spanned(template, msg));
+                    }
+                }
+                format.push_str("{}");
+            }
+            '}' => return Err(Error::new_spanned(template, "unmatched `}` in self template")),
+            c => format.push(c),
+        }
+    }
+
+    Ok((format, args))
+}
@@ -0,0 +1,178 @@
+//! Compact URIs (curies) for link relations.
+//!
+//! A curie is a `curies` link whose `name` is a prefix and whose href is a
+//! URI Template with a `{rel}` variable, such as
+//! `http://example.com/docs/rels/{rel}`. The rel `ea:order` then stands for
+//! `http://example.com/docs/rels/order`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use error::pointer;
+use link::Link;
+use resource::Resource;
+use state::HalState;
+use uri_template::UriTemplate;
+
+/// Splits a rel such as `ea:order` into its prefix and reference
+pub fn split(rel: &str) -> Option<(&str, &str)> {
+    let colon = rel.find(':')?;
+    let (prefix, reference) = (&rel[..colon], &rel[colon + 1..]);
+    if prefix.is_empty() || reference.starts_with("//") {
+        return None;
+    }
+    Some((prefix, reference))
+}
+
+/// Expands the reference part of a curie rel with a curie link
+pub(crate) fn expand(curie: &Link, reference: &str) -> Option<String> {
+    let template = UriTemplate::parse(&curie.href).ok()?;
+    let mut vars = BTreeMap::new();
+    vars.insert("rel".to_string(), HalState::String(reference.to_string()));
+    Some(template.expand(&vars))
+}
+
+/// Finds the reference a curie link would expand into the given URI
+pub(crate) fn compact<'a>(curie: &Link, uri: &'a str) -> Option<&'a str> {
+    let start = curie.href.find("{rel}")?;
+    let (before, after) = (&curie.href[..start], &curie.href[start + "{rel}".len()..]);
+    if after.contains('{') || uri.len() <= before.len() + after.len() {
+        return None;
+    }
+    if uri.starts_with(before) && uri.ends_with(after) {
+        Some(&uri[before.len()..uri.len() - after.len()])
+    } else {
+        None
+    }
+}
+
+/// A problem found by `Resource::validate_curies`
+#[derive(Clone, PartialEq, Debug)]
+pub struct CurieIssue {
+    /// The JSON Pointer of the offending link, curie or embedded rel
+    pub path: String,
+    pub kind: CurieIssueKind,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum CurieIssueKind {
+    /// A rel uses a prefix that no curie in scope declares
+    UndeclaredPrefix(String),
+    /// A resource declares the same curie name more than once
+    DuplicateName(String),
+    /// An embedded resource declares a curie name in scope with another href
+    Redefined { name: String, href: String, previous: String },
+    /// A curie has no name
+    MissingName,
+    /// A curie is not marked as templated
+    NotTemplated,
+    /// A curie href does not contain the `{rel}` variable
+    MissingRelVariable,
+}
+
+impl fmt::Display for CurieIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CurieIssueKind::UndeclaredPrefix(ref prefix) => {
+                write!(f, "curie prefix `{}` is not declared", prefix)
+            }
+            CurieIssueKind::DuplicateName(ref name) => {
+                write!(f, "curie `{}` is declared more than once", name)
+            }
+            CurieIssueKind::Redefined { ref name, ref href, ref previous } => {
+                write!(f, "curie `{}` is redefined as `{}`, was `{}`", name, href, previous)
+            }
+            CurieIssueKind::MissingName => write!(f, "curie has no name"),
+            CurieIssueKind::NotTemplated => write!(f, "curie is not templated"),
+            CurieIssueKind::MissingRelVariable => write!(f, "curie href does not contain `{{rel}}`"),
+        }
+    }
+}
+
+impl fmt::Display for CurieIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at `{}`", self.kind, self.path)
+    }
+}
+
+/// Checks the curies of a resource and every resource embedded in it
+///
+/// Curies declared on a resource are in scope for the resources it embeds.
+pub fn validate(resource: &Resource) -> Vec<CurieIssue> {
+    let mut issues = Vec::new();
+    validate_at(resource, "", &BTreeMap::new(), &mut issues);
+    issues
+}
+
+fn validate_at(resource: &Resource,
+               path: &str,
+               outer: &BTreeMap<&str, &str>,
+               issues: &mut Vec<CurieIssue>) {
+    let links_path = pointer(path, "_links");
+    let curies_path = pointer(&links_path, "curies");
+    let mut scope = outer.clone();
+    let mut declared = BTreeSet::new();
+
+    for (i, curie) in resource.get_links("curies").iter().enumerate() {
+        let curie_path = pointer(&curies_path, &i.to_string());
+        let mut issue = |kind| issues.push(CurieIssue { path: curie_path.clone(), kind });
+
+        if curie.templated != Some(true) {
+            issue(CurieIssueKind::NotTemplated);
+        }
+        let has_rel = UriTemplate::parse(&curie.href)
+            .map(|template| template.variables().contains(&"rel"))
+            .unwrap_or(false);
+        if !has_rel {
+            issue(CurieIssueKind::MissingRelVariable);
+        }
+
+        let name = match curie.name {
+            Some(ref name) => &name[..],
+            None => {
+                issue(CurieIssueKind::MissingName);
+                continue;
+            }
+        };
+        if !declared.insert(name) {
+            issue(CurieIssueKind::DuplicateName(name.to_string()));
+        }
+        if let Some(&previous) = outer.get(name) {
+            if previous != curie.href {
+                issue(CurieIssueKind::Redefined {
+                    name: name.to_string(),
+                    href: curie.href.clone(),
+                    previous: previous.to_string(),
+                });
+            }
+        }
+        scope.insert(name, &curie.href[..]);
+    }
+
+    let mut check_rel = |rel: &str, rel_path: String| {
+        if let Some((prefix, _)) = split(rel) {
+            if !scope.contains_key(prefix) {
+                issues.push(CurieIssue {
+                    path: rel_path,
+                    kind: CurieIssueKind::UndeclaredPrefix(prefix.to_string()),
+                });
+            }
+        }
+    };
+
+    for rel in resource.link_rels() {
+        check_rel(rel, pointer(&links_path, rel));
+    }
+
+    let embedded_path = pointer(path, "_embedded");
+    for rel in resource.embedded_rels() {
+        check_rel(rel, pointer(&embedded_path, rel));
+    }
+
+    for (rel, resources) in resource.embedded() {
+        let rel_path = pointer(&embedded_path, rel);
+        for (i, embedded) in resources.iter().enumerate() {
+            validate_at(embedded, &resource.embedded_pointer(&rel_path, rel, i), &scope, issues);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::error::Error;
+use std::fmt;
+use serialize::json::{Json, Object};
+
+/// An error produced while reading or building a Hal document
+///
+/// Every error carries the location it refers to as a JSON Pointer (RFC 6901),
+/// such as `/_links/self/href`. The empty string refers to the document root.
+#[derive(Clone, PartialEq, Debug)]
+pub enum HalError {
+    /// A required member of an object is missing
+    MissingField { path: String, field: String },
+    /// A value does not have the expected json type
+    WrongType { path: String, expected: &'static str, found: &'static str },
+    /// A value could not be converted into Hal data
+    Serialize(String),
+    /// A state key is one of the keys Hal reserves for itself
+    ReservedKey(String),
+    /// A URI Template is malformed at the given byte offset
+    InvalidTemplate { template: String, position: usize, reason: &'static str },
+    /// The input is not valid json
+    Syntax { line: usize, column: usize, reason: &'static str },
+}
+
+impl HalError {
+    /// The JSON Pointer of the value this error refers to
+    pub fn path(&self) -> &str {
+        match *self {
+            HalError::MissingField { ref path, .. } => path,
+            HalError::WrongType { ref path, .. } => path,
+            HalError::Serialize(_) => "",
+            HalError::ReservedKey(_) => "",
+            HalError::InvalidTemplate { .. } => "",
+            HalError::Syntax { .. } => "",
+        }
+    }
+
+    /// Prefixes the path of this error with the JSON Pointer of the value
+    /// that contains it
+    pub fn nested_in(self, parent: &str) -> HalError {
+        match self {
+            HalError::MissingField { path, field } => {
+                HalError::MissingField { path: format!("{}{}", parent, path), field }
+            }
+            HalError::WrongType { path, expected, found } => {
+                HalError::WrongType { path: format!("{}{}", parent, path), expected, found }
+            }
+            HalError::Serialize(msg) => HalError::Serialize(msg),
+            HalError::ReservedKey(key) => HalError::ReservedKey(key),
+            e @ HalError::InvalidTemplate { .. } => e,
+            e @ HalError::Syntax { .. } => e,
+        }
+    }
+}
+
+impl fmt::Display for HalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HalError::MissingField { ref path, ref field } => {
+                write!(f, "missing field `{}` at `{}`", field, path)
+            }
+            HalError::WrongType { ref path, expected, found } => {
+                write!(f, "expected {} at `{}`, found {}", expected, path, found)
+            }
+            HalError::Serialize(ref msg) => write!(f, "{}", msg),
+            HalError::ReservedKey(ref key) => {
+                write!(f, "`{}` is reserved by Hal and cannot be used as a state key", key)
+            }
+            HalError::InvalidTemplate { ref template, position, reason } => {
+                write!(f, "invalid URI Template `{}` at offset {}: {}", template, position, reason)
+            }
+            HalError::Syntax { line, column, reason } => {
+                write!(f, "invalid json at line {}, column {}: {}", line, column, reason)
+            }
+        }
+    }
+}
+
+impl Error for HalError {}
+
+/// Appends a reference token to a JSON Pointer, escaping it as RFC 6901 requires
+pub(crate) fn pointer(parent: &str, token: &str) -> String {
+    format!("{}/{}", parent, token.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Names the json type of a value for use in error messages
+pub(crate) fn json_type(json: &Json) -> &'static str {
+    match *json {
+        Json::I64(_) | Json::U64(_) | Json::F64(_) => "number",
+        Json::String(_) => "string",
+        Json::Boolean(_) => "boolean",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+        Json::Null => "null",
+    }
+}
+
+/// Borrows a value as a json object or reports what was found instead
+pub(crate) fn object_at<'a>(json: &'a Json, path: &str) -> Result<&'a Object, HalError> {
+    match *json {
+        Json::Object(ref object) => Ok(object),
+        ref other => Err(HalError::WrongType {
+            path: path.to_string(),
+            expected: "object",
+            found: json_type(other),
+        }),
+    }
+}
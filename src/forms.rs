@@ -0,0 +1,1081 @@
+//! HAL-FORMS templates.
+//!
+//! HAL-FORMS (`application/prs.hal-forms+json`) extends Hal with a
+//! `_templates` member that describes the actions a client can take on a
+//! resource: the HTTP method, the target, the content type of the request
+//! and the properties it is made of. A resource without templates is
+//! written as plain Hal, and `Resource::parse` reads `_templates` as state;
+//! `Resource::parse_with` and `ParseOptions::forms` read it as templates.
+//!
+//! The values a property may take are listed inline or fetched from a link,
+//! and `Template::validate` checks a submitted payload against the
+//! properties of a template.
+//!
+//! ```
+//! extern crate hal;
+//! extern crate rustc_serialize as serialize;
+//!
+//! use hal::forms::{Property, Template};
+//! use hal::resource::Resource;
+//! use serialize::json::ToJson;
+//!
+//! fn main() {
+//!     let mut hal = Resource::with_self("/orders/123");
+//!     hal.add_template("default", Template::new("PUT")
+//!         .title("Update the order")
+//!         .property(Property::new("status").required(true).options(vec!["processing", "shipped"]))
+//!         .property(Property::new("total").input_type("number").min(0.0)));
+//!
+//!     let template = hal.get_template("default").unwrap();
+//!     assert_eq!(template.get_method(), "PUT");
+//!     assert_eq!(template.get_property("status").and_then(|p| p.get_required()), Some(true));
+//!     assert!(hal.to_json().find("_templates").is_some());
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use serialize::json::{ToJson, Json, Object};
+use serialize::{Encodable, Encoder};
+
+use error::{HalError, json_type, object_at, pointer};
+use link::{optional_string_at, string_at, Link};
+
+/// The media type of Hal documents with HAL-FORMS templates
+pub const MEDIA_TYPE: &str = "application/prs.hal-forms+json";
+
+/// A HAL-FORMS template
+#[derive(Clone, PartialEq, Debug)]
+pub struct Template {
+    method: String,
+    target: Option<String>,
+    content_type: Option<String>,
+    title: Option<String>,
+    properties: Vec<Property>,
+}
+
+/// A property of a HAL-FORMS template, one field of the request
+#[derive(Clone, PartialEq, Debug)]
+pub struct Property {
+    name: String,
+    prompt: Option<String>,
+    input_type: Option<String>,
+    value: Option<String>,
+    placeholder: Option<String>,
+    required: Option<bool>,
+    read_only: Option<bool>,
+    regex: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    min_length: Option<u64>,
+    max_length: Option<u64>,
+    step: Option<f64>,
+    options: Option<Options>,
+}
+
+/// The values a property may take, and how many of them may be selected
+#[derive(Clone, PartialEq, Debug)]
+pub struct Options {
+    source: OptionSource,
+    selected_values: Vec<String>,
+    prompt_field: Option<String>,
+    value_field: Option<String>,
+    min_items: Option<u64>,
+    max_items: Option<u64>,
+}
+
+/// Where the values of a property come from
+#[derive(Clone, PartialEq, Debug)]
+pub enum OptionSource {
+    /// The values are listed in the template
+    Inline(Vec<Choice>),
+    /// The values are fetched from another resource, which returns them as
+    /// a json array
+    Link(Link),
+}
+
+/// One of the values a property may take
+///
+/// A choice without a prompt is written as a plain string.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Choice {
+    prompt: Option<String>,
+    value: String,
+}
+
+impl Template {
+    /// Create a template for requests with the given HTTP method
+    pub fn new<S: Into<String>>(method: S) -> Template {
+        Template {
+            method: method.into(),
+            target: None,
+            content_type: None,
+            title: None,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Convert a json object into a Template, reporting any member that is
+    /// missing or has the wrong type
+    pub fn parse(json: &Json) -> Result<Template, HalError> {
+        Template::parse_at(json, "")
+    }
+
+    pub(crate) fn parse_at(json: &Json, path: &str) -> Result<Template, HalError> {
+        let object = object_at(json, path)?;
+
+        let mut template = match object.get("method") {
+            Some(value) => Template::new(string_at(value, path, "method")?),
+            None => {
+                return Err(HalError::MissingField {
+                    path: path.to_string(),
+                    field: "method".to_string(),
+                })
+            }
+        };
+
+        template.target = optional_string_at(object, path, "target")?;
+        template.content_type = optional_string_at(object, path, "contentType")?;
+        template.title = optional_string_at(object, path, "title")?;
+
+        if let Some(value) = object.get("properties") {
+            let properties_path = pointer(path, "properties");
+            match *value {
+                Json::Array(ref values) => {
+                    for (i, value) in values.iter().enumerate() {
+                        let property = Property::parse_at(value, &pointer(&properties_path, &i.to_string()))?;
+                        template.properties.push(property);
+                    }
+                }
+                ref other => return Err(wrong_type(properties_path, "array", other)),
+            }
+        }
+
+        Ok(template)
+    }
+
+    pub fn get_method(&self) -> &str {
+        &self.method
+    }
+
+    /// Get the URI the request is sent to; without one it is sent to the
+    /// resource itself
+    pub fn get_target(&self) -> Option<&str> {
+        self.target.as_ref().map(|s| &s[..])
+    }
+
+    /// Get the media type of the request body; without one it is
+    /// `application/json`
+    pub fn get_content_type(&self) -> Option<&str> {
+        self.content_type.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_properties(&self) -> &[Property] {
+        &self.properties
+    }
+
+    /// Get the property with the given name
+    pub fn get_property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| property.name == name)
+    }
+
+    pub fn target<S: Into<String>>(&mut self, target: S) -> &mut Template {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn content_type<S: Into<String>>(&mut self, content_type: S) -> &mut Template {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn title<S: Into<String>>(&mut self, title: S) -> &mut Template {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Check a submitted payload against the properties of this template
+    ///
+    /// Each property is checked for presence, type, range, length, regular
+    /// expression and options, and a read-only property for keeping its
+    /// value; members the template does not describe are ignored. Options
+    /// fetched from a link cannot be checked, and regular expressions are
+    /// only checked with the `regex` feature. The violations are in the
+    /// order of the properties.
+    pub fn validate(&self, payload: &Json) -> Vec<Violation> {
+        let object = match *payload {
+            Json::Object(ref object) => object,
+            _ => return vec![Violation { path: String::new(), kind: ViolationKind::NotAnObject }],
+        };
+
+        let mut violations = Vec::new();
+        for property in &self.properties {
+            property.validate(object.get(&property.name), &pointer("", &property.name), &mut violations);
+        }
+        violations
+    }
+
+    /// Add a property, replacing any property with the same name
+    pub fn property<P: Into<Property>>(&mut self, property: P) -> &mut Template {
+        let property = property.into();
+        match self.properties.iter().position(|p| p.name == property.name) {
+            Some(i) => self.properties[i] = property,
+            None => self.properties.push(property),
+        }
+        self
+    }
+}
+
+impl Property {
+    /// Create a property for the request field with the given name
+    pub fn new<S: Into<String>>(name: S) -> Property {
+        Property {
+            name: name.into(),
+            prompt: None,
+            input_type: None,
+            value: None,
+            placeholder: None,
+            required: None,
+            read_only: None,
+            regex: None,
+            min: None,
+            max: None,
+            min_length: None,
+            max_length: None,
+            step: None,
+            options: None,
+        }
+    }
+
+    /// Convert a json object into a Property, reporting any member that is
+    /// missing or has the wrong type
+    pub fn parse(json: &Json) -> Result<Property, HalError> {
+        Property::parse_at(json, "")
+    }
+
+    pub(crate) fn parse_at(json: &Json, path: &str) -> Result<Property, HalError> {
+        let object = object_at(json, path)?;
+
+        let mut property = match object.get("name") {
+            Some(value) => Property::new(string_at(value, path, "name")?),
+            None => {
+                return Err(HalError::MissingField {
+                    path: path.to_string(),
+                    field: "name".to_string(),
+                })
+            }
+        };
+
+        property.prompt = optional_string_at(object, path, "prompt")?;
+        property.input_type = optional_string_at(object, path, "type")?;
+        property.value = optional_string_at(object, path, "value")?;
+        property.placeholder = optional_string_at(object, path, "placeholder")?;
+        property.required = optional_bool_at(object, path, "required")?;
+        property.read_only = optional_bool_at(object, path, "readOnly")?;
+        property.regex = optional_string_at(object, path, "regex")?;
+        property.min = optional_f64_at(object, path, "min")?;
+        property.max = optional_f64_at(object, path, "max")?;
+        property.min_length = optional_u64_at(object, path, "minLength")?;
+        property.max_length = optional_u64_at(object, path, "maxLength")?;
+        property.step = optional_f64_at(object, path, "step")?;
+
+        if let Some(value) = object.get("options") {
+            property.options = Some(Options::parse_at(value, &pointer(path, "options"))?);
+        }
+
+        Ok(property)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the text shown to the user for the property
+    pub fn get_prompt(&self) -> Option<&str> {
+        self.prompt.as_ref().map(|s| &s[..])
+    }
+
+    /// Get the kind of input, named after the HTML input types such as
+    /// `number` or `email`
+    pub fn get_input_type(&self) -> Option<&str> {
+        self.input_type.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_value(&self) -> Option<&str> {
+        self.value.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_placeholder(&self) -> Option<&str> {
+        self.placeholder.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_required(&self) -> Option<bool> {
+        self.required
+    }
+
+    pub fn get_read_only(&self) -> Option<bool> {
+        self.read_only
+    }
+
+    pub fn get_regex(&self) -> Option<&str> {
+        self.regex.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn get_max(&self) -> Option<f64> {
+        self.max
+    }
+
+    pub fn get_min_length(&self) -> Option<u64> {
+        self.min_length
+    }
+
+    pub fn get_max_length(&self) -> Option<u64> {
+        self.max_length
+    }
+
+    pub fn get_step(&self) -> Option<f64> {
+        self.step
+    }
+
+    /// Get the values the property may take, if they are listed inline
+    /// rather than fetched from a link
+    pub fn get_options(&self) -> Option<&[Choice]> {
+        self.options.as_ref().and_then(Options::get_inline)
+    }
+
+    /// Get the options element of the property, with where its values come
+    /// from and how many may be selected
+    pub fn get_options_element(&self) -> Option<&Options> {
+        self.options.as_ref()
+    }
+
+    pub fn prompt<S: Into<String>>(&mut self, prompt: S) -> &mut Property {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn input_type<S: Into<String>>(&mut self, input_type: S) -> &mut Property {
+        self.input_type = Some(input_type.into());
+        self
+    }
+
+    pub fn value<S: Into<String>>(&mut self, value: S) -> &mut Property {
+        self.value = Some(value.into());
+        self
+    }
+
+    pub fn placeholder<S: Into<String>>(&mut self, placeholder: S) -> &mut Property {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn required(&mut self, required: bool) -> &mut Property {
+        self.required = Some(required);
+        self
+    }
+
+    pub fn read_only(&mut self, read_only: bool) -> &mut Property {
+        self.read_only = Some(read_only);
+        self
+    }
+
+    /// Set the regular expression a value must match
+    pub fn regex<S: Into<String>>(&mut self, regex: S) -> &mut Property {
+        self.regex = Some(regex.into());
+        self
+    }
+
+    pub fn min(&mut self, min: f64) -> &mut Property {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(&mut self, max: f64) -> &mut Property {
+        self.max = Some(max);
+        self
+    }
+
+    pub fn min_length(&mut self, min_length: u64) -> &mut Property {
+        self.min_length = Some(min_length);
+        self
+    }
+
+    pub fn max_length(&mut self, max_length: u64) -> &mut Property {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn step(&mut self, step: f64) -> &mut Property {
+        self.step = Some(step);
+        self
+    }
+
+    /// Set the values the property may take
+    ///
+    /// A list of values is taken as inline options.
+    pub fn options<O: Into<Options>>(&mut self, options: O) -> &mut Property {
+        self.options = Some(options.into());
+        self
+    }
+
+    /// Whether a payload must have a value for the property
+    ///
+    /// A property is required when it says so or when at least one of its
+    /// options must be selected.
+    pub fn is_required(&self) -> bool {
+        self.required == Some(true) ||
+        self.options.as_ref().and_then(|options| options.min_items).is_some_and(|min| min > 0)
+    }
+}
+
+/// Checks a submitted value
+impl Property {
+    fn validate(&self, value: Option<&Json>, path: &str, violations: &mut Vec<Violation>) {
+        let violation = |kind| Violation { path: path.to_string(), kind };
+
+        let value = match value {
+            None | Some(&Json::Null) => {
+                if self.is_required() {
+                    violations.push(violation(ViolationKind::Missing));
+                }
+                return;
+            }
+            Some(value) => value,
+        };
+
+        if self.read_only == Some(true) {
+            if let Some(ref expected) = self.value {
+                if text(value).as_ref() != Some(expected) {
+                    violations.push(violation(ViolationKind::ReadOnly));
+                }
+            }
+        }
+
+        let items = match *value {
+            Json::Array(ref items) if self.options.is_some() => Some(&items[..]),
+            _ => None,
+        };
+
+        if let Some(ref options) = self.options {
+            // A single value is one selected item
+            let count = items.map_or(1, |items| items.len() as u64);
+            if let Some(min_items) = options.min_items.filter(|&min| count < min) {
+                violations.push(violation(ViolationKind::TooFewItems { min_items }));
+            }
+            if let Some(max_items) = options.max_items.filter(|&max| count > max) {
+                violations.push(violation(ViolationKind::TooManyItems { max_items }));
+            }
+        }
+
+        match items {
+            Some(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    self.validate_item(item, &pointer(path, &i.to_string()), violations);
+                }
+            }
+            None => self.validate_item(value, path, violations),
+        }
+    }
+
+    /// Checks a single value, or one of the values selected from options
+    fn validate_item(&self, value: &Json, path: &str, violations: &mut Vec<Violation>) {
+        let mut push = |kind| violations.push(Violation { path: path.to_string(), kind });
+
+        let text = match text(value) {
+            Some(text) => text,
+            None => {
+                let expected = if self.options.is_some() { "string or array" } else { "string" };
+                push(ViolationKind::WrongType { expected });
+                return;
+            }
+        };
+
+        let numeric = self.min.is_some() || self.max.is_some() ||
+                      self.input_type.as_ref().is_some_and(|t| t == "number" || t == "range");
+        if numeric {
+            match value.as_f64().or_else(|| text.parse().ok()) {
+                Some(number) => {
+                    if let Some(min) = self.min.filter(|&min| number < min) {
+                        push(ViolationKind::BelowMin { min });
+                    }
+                    if let Some(max) = self.max.filter(|&max| number > max) {
+                        push(ViolationKind::AboveMax { max });
+                    }
+                }
+                None => push(ViolationKind::WrongType { expected: "number" }),
+            }
+        }
+
+        let length = text.chars().count() as u64;
+        if let Some(min_length) = self.min_length.filter(|&min| length < min) {
+            push(ViolationKind::TooShort { min_length });
+        }
+        if let Some(max_length) = self.max_length.filter(|&max| length > max) {
+            push(ViolationKind::TooLong { max_length });
+        }
+
+        if let Some(ref regex) = self.regex {
+            if let Some(kind) = check_regex(regex, &text) {
+                push(kind);
+            }
+        }
+
+        if let Some(choices) = self.options.as_ref().and_then(Options::get_inline) {
+            if !choices.iter().any(|choice| choice.value == text) {
+                push(ViolationKind::NotAnOption { value: text });
+            }
+        }
+    }
+}
+
+impl Options {
+    /// Options listed in the template
+    pub fn inline<C: Into<Choice>>(choices: Vec<C>) -> Options {
+        Options::new(OptionSource::Inline(choices.into_iter().map(Into::into).collect()))
+    }
+
+    /// Options fetched from the resource a link points to
+    pub fn link<L: Into<Link>>(link: L) -> Options {
+        Options::new(OptionSource::Link(link.into()))
+    }
+
+    fn new(source: OptionSource) -> Options {
+        Options {
+            source,
+            selected_values: Vec::new(),
+            prompt_field: None,
+            value_field: None,
+            min_items: None,
+            max_items: None,
+        }
+    }
+
+    pub(crate) fn parse_at(json: &Json, path: &str) -> Result<Options, HalError> {
+        let object = object_at(json, path)?;
+
+        let prompt_field = optional_string_at(object, path, "promptField")?;
+        let value_field = optional_string_at(object, path, "valueField")?;
+
+        // HAL-FORMS prefers inline options when both are given
+        let source = match (object.get("inline"), object.get("link")) {
+            (Some(value), _) => {
+                let inline_path = pointer(path, "inline");
+                let fields = (prompt_field.as_deref().unwrap_or("prompt"), value_field.as_deref().unwrap_or("value"));
+                OptionSource::Inline(Choice::parse_list(value, &inline_path, fields)?)
+            }
+            (None, Some(value)) => OptionSource::Link(Link::parse_at(value, &pointer(path, "link"))?),
+            (None, None) => {
+                return Err(HalError::MissingField {
+                    path: path.to_string(),
+                    field: "inline".to_string(),
+                })
+            }
+        };
+
+        let mut options = Options::new(source);
+        options.prompt_field = prompt_field;
+        options.value_field = value_field;
+        options.min_items = optional_u64_at(object, path, "minItems")?;
+        options.max_items = optional_u64_at(object, path, "maxItems")?;
+
+        if let Some(value) = object.get("selectedValues") {
+            let values_path = pointer(path, "selectedValues");
+            match *value {
+                Json::Array(ref values) => {
+                    for (i, value) in values.iter().enumerate() {
+                        match *value {
+                            Json::String(ref value) => options.selected_values.push(value.clone()),
+                            ref other => return Err(wrong_type(pointer(&values_path, &i.to_string()), "string", other)),
+                        }
+                    }
+                }
+                ref other => return Err(wrong_type(values_path, "array", other)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    pub fn get_source(&self) -> &OptionSource {
+        &self.source
+    }
+
+    /// Get the options listed in the template, if they are not fetched
+    pub fn get_inline(&self) -> Option<&[Choice]> {
+        match self.source {
+            OptionSource::Inline(ref choices) => Some(choices),
+            OptionSource::Link(_) => None,
+        }
+    }
+
+    /// Get the link the options are fetched from, if they are not inline
+    pub fn get_link(&self) -> Option<&Link> {
+        match self.source {
+            OptionSource::Link(ref link) => Some(link),
+            OptionSource::Inline(_) => None,
+        }
+    }
+
+    /// Get the values selected to begin with
+    pub fn get_selected_values(&self) -> &[String] {
+        &self.selected_values
+    }
+
+    /// Get the member of each option object that holds its prompt; without
+    /// one it is `prompt`
+    pub fn get_prompt_field(&self) -> Option<&str> {
+        self.prompt_field.as_ref().map(|s| &s[..])
+    }
+
+    /// Get the member of each option object that holds its value; without
+    /// one it is `value`
+    pub fn get_value_field(&self) -> Option<&str> {
+        self.value_field.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_min_items(&self) -> Option<u64> {
+        self.min_items
+    }
+
+    pub fn get_max_items(&self) -> Option<u64> {
+        self.max_items
+    }
+
+    /// Whether more than one value may be selected
+    pub fn is_multiple(&self) -> bool {
+        self.max_items.unwrap_or(u64::MAX) > 1
+    }
+
+    /// Select a value to begin with
+    pub fn selected_value<S: Into<String>>(&mut self, value: S) -> &mut Options {
+        self.selected_values.push(value.into());
+        self
+    }
+
+    pub fn prompt_field<S: Into<String>>(&mut self, prompt_field: S) -> &mut Options {
+        self.prompt_field = Some(prompt_field.into());
+        self
+    }
+
+    pub fn value_field<S: Into<String>>(&mut self, value_field: S) -> &mut Options {
+        self.value_field = Some(value_field.into());
+        self
+    }
+
+    /// Set the least number of values that must be selected
+    pub fn min_items(&mut self, min_items: u64) -> &mut Options {
+        self.min_items = Some(min_items);
+        self
+    }
+
+    /// Set the most values that may be selected; without a limit, any
+    /// number may be
+    pub fn max_items(&mut self, max_items: u64) -> &mut Options {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Read the options returned by the resource the link points to
+    ///
+    /// The response is an array of strings, or of objects whose prompt and
+    /// value are in the members named by `promptField` and `valueField`.
+    pub fn read_linked(&self, json: &Json) -> Result<Vec<Choice>, HalError> {
+        Choice::parse_list(json, "", self.fields())
+    }
+
+    /// The members of option objects that hold their prompt and value
+    fn fields(&self) -> (&str, &str) {
+        (self.get_prompt_field().unwrap_or("prompt"), self.get_value_field().unwrap_or("value"))
+    }
+}
+
+impl Choice {
+    pub fn new<S: Into<String>>(value: S) -> Choice {
+        Choice { prompt: None, value: value.into() }
+    }
+
+    /// Reads an array of options, whose objects keep their prompt and value
+    /// in the given members
+    fn parse_list(json: &Json, path: &str, fields: (&str, &str)) -> Result<Vec<Choice>, HalError> {
+        match *json {
+            Json::Array(ref values) => {
+                values.iter()
+                    .enumerate()
+                    .map(|(i, value)| Choice::parse_at(value, &pointer(path, &i.to_string()), fields))
+                    .collect()
+            }
+            ref other => Err(wrong_type(path.to_string(), "array", other)),
+        }
+    }
+
+    fn parse_at(json: &Json, path: &str, (prompt_field, value_field): (&str, &str)) -> Result<Choice, HalError> {
+        match *json {
+            Json::String(ref value) => Ok(Choice::new(&value[..])),
+            Json::Object(ref object) => {
+                let value = match object.get(value_field) {
+                    Some(value) => string_at(value, path, value_field)?,
+                    None => {
+                        return Err(HalError::MissingField {
+                            path: path.to_string(),
+                            field: value_field.to_string(),
+                        })
+                    }
+                };
+                Ok(Choice { prompt: optional_string_at(object, path, prompt_field)?, value })
+            }
+            ref other => Err(wrong_type(path.to_string(), "string or object", other)),
+        }
+    }
+
+    /// Writes the choice, as an object with the given members if it has a
+    /// prompt
+    fn to_json_with(&self, (prompt_field, value_field): (&str, &str)) -> Json {
+        match self.prompt {
+            Some(ref prompt) => {
+                let mut choice = BTreeMap::new();
+                choice.insert(prompt_field.to_string(), prompt.to_json());
+                choice.insert(value_field.to_string(), self.value.to_json());
+                Json::Object(choice)
+            }
+            None => self.value.to_json(),
+        }
+    }
+
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    /// Get the text shown to the user for the choice
+    pub fn get_prompt(&self) -> Option<&str> {
+        self.prompt.as_ref().map(|s| &s[..])
+    }
+
+    pub fn prompt<S: Into<String>>(&mut self, prompt: S) -> &mut Choice {
+        self.prompt = Some(prompt.into());
+        self
+    }
+}
+
+impl ToJson for Template {
+    fn to_json(&self) -> Json {
+        let mut template = BTreeMap::new();
+        template.insert("method".to_string(), self.method.to_json());
+
+        if self.target.is_some() {
+            template.insert("target".to_string(), self.target.to_json());
+        }
+
+        if self.content_type.is_some() {
+            template.insert("contentType".to_string(), self.content_type.to_json());
+        }
+
+        if self.title.is_some() {
+            template.insert("title".to_string(), self.title.to_json());
+        }
+
+        if !self.properties.is_empty() {
+            template.insert("properties".to_string(), self.properties.to_json());
+        }
+
+        Json::Object(template)
+    }
+}
+
+impl ToJson for Property {
+    fn to_json(&self) -> Json {
+        let mut property = BTreeMap::new();
+        property.insert("name".to_string(), self.name.to_json());
+
+        let strings = [("prompt", &self.prompt),
+                       ("type", &self.input_type),
+                       ("value", &self.value),
+                       ("placeholder", &self.placeholder),
+                       ("regex", &self.regex)];
+        for &(key, value) in strings.iter() {
+            if let Some(ref value) = *value {
+                property.insert(key.to_string(), value.to_json());
+            }
+        }
+
+        let flags = [("required", self.required), ("readOnly", self.read_only)];
+        for &(key, value) in flags.iter() {
+            if let Some(value) = value {
+                property.insert(key.to_string(), Json::Boolean(value));
+            }
+        }
+
+        let numbers = [("min", self.min), ("max", self.max), ("step", self.step)];
+        for &(key, value) in numbers.iter() {
+            if let Some(value) = value {
+                property.insert(key.to_string(), Json::F64(value));
+            }
+        }
+
+        let lengths = [("minLength", self.min_length), ("maxLength", self.max_length)];
+        for &(key, value) in lengths.iter() {
+            if let Some(value) = value {
+                property.insert(key.to_string(), Json::U64(value));
+            }
+        }
+
+        if let Some(ref options) = self.options {
+            property.insert("options".to_string(), options.to_json());
+        }
+
+        Json::Object(property)
+    }
+}
+
+impl ToJson for Options {
+    fn to_json(&self) -> Json {
+        let mut options = BTreeMap::new();
+
+        match self.source {
+            OptionSource::Inline(ref choices) => {
+                let fields = self.fields();
+                let choices = choices.iter().map(|choice| choice.to_json_with(fields)).collect();
+                options.insert("inline".to_string(), Json::Array(choices));
+            }
+            OptionSource::Link(ref link) => {
+                options.insert("link".to_string(), link.to_json());
+            }
+        }
+
+        if !self.selected_values.is_empty() {
+            options.insert("selectedValues".to_string(), self.selected_values.to_json());
+        }
+
+        if self.prompt_field.is_some() {
+            options.insert("promptField".to_string(), self.prompt_field.to_json());
+        }
+
+        if self.value_field.is_some() {
+            options.insert("valueField".to_string(), self.value_field.to_json());
+        }
+
+        if let Some(min_items) = self.min_items {
+            options.insert("minItems".to_string(), Json::U64(min_items));
+        }
+
+        if let Some(max_items) = self.max_items {
+            options.insert("maxItems".to_string(), Json::U64(max_items));
+        }
+
+        Json::Object(options)
+    }
+}
+
+impl ToJson for Choice {
+    fn to_json(&self) -> Json {
+        self.to_json_with(("prompt", "value"))
+    }
+}
+
+/// Writes the same json as `to_json`; templates are small, so this goes
+/// through `to_json` rather than being written piece by piece
+impl Encodable for Template {
+    fn encode<E: Encoder>(&self, e: &mut E) -> Result<(), E::Error> {
+        self.to_json().encode(e)
+    }
+}
+
+impl<'a> From<&'a Template> for Template {
+    fn from(template: &'a Template) -> Template {
+        template.clone()
+    }
+}
+
+impl<'a> From<&'a mut Template> for Template {
+    fn from(template: &'a mut Template) -> Template {
+        template.clone()
+    }
+}
+
+impl TryFrom<Json> for Template {
+    type Error = HalError;
+
+    fn try_from(json: Json) -> Result<Template, HalError> {
+        Template::parse(&json)
+    }
+}
+
+impl<'a> From<&'a Property> for Property {
+    fn from(property: &'a Property) -> Property {
+        property.clone()
+    }
+}
+
+impl<'a> From<&'a mut Property> for Property {
+    fn from(property: &'a mut Property) -> Property {
+        property.clone()
+    }
+}
+
+impl<C: Into<Choice>> From<Vec<C>> for Options {
+    fn from(choices: Vec<C>) -> Options {
+        Options::inline(choices)
+    }
+}
+
+impl<'a> From<&'a mut Options> for Options {
+    fn from(options: &'a mut Options) -> Options {
+        options.clone()
+    }
+}
+
+impl From<String> for Choice {
+    fn from(value: String) -> Choice {
+        Choice::new(value)
+    }
+}
+
+impl<'a> From<&'a str> for Choice {
+    fn from(value: &'a str) -> Choice {
+        Choice::new(value)
+    }
+}
+
+impl<'a> From<&'a mut Choice> for Choice {
+    fn from(choice: &'a mut Choice) -> Choice {
+        choice.clone()
+    }
+}
+
+/// A submitted value that a template does not allow
+#[derive(Clone, PartialEq, Debug)]
+pub struct Violation {
+    /// The JSON Pointer of the value in the payload
+    pub path: String,
+    pub kind: ViolationKind,
+}
+
+/// What is wrong with a submitted value
+#[derive(Clone, PartialEq, Debug)]
+pub enum ViolationKind {
+    /// The payload is not a json object
+    NotAnObject,
+    /// A required property has no value
+    Missing,
+    /// A read-only property has a value other than the one it was given
+    ReadOnly,
+    /// A value has the wrong json type
+    WrongType { expected: &'static str },
+    BelowMin { min: f64 },
+    AboveMax { max: f64 },
+    TooShort { min_length: u64 },
+    TooLong { max_length: u64 },
+    /// A value does not match the regular expression of its property
+    NoMatch { regex: String },
+    /// The regular expression of a property is not valid
+    InvalidRegex { regex: String },
+    /// The regular expression of a property was not checked, as the `regex`
+    /// feature is not enabled
+    Unchecked { regex: String },
+    /// A value is not one of the inline options of its property
+    NotAnOption { value: String },
+    TooFewItems { min_items: u64 },
+    TooManyItems { max_items: u64 },
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ViolationKind::NotAnObject => write!(f, "payload is not an object"),
+            ViolationKind::Missing => write!(f, "value is required"),
+            ViolationKind::ReadOnly => write!(f, "value is read-only"),
+            ViolationKind::WrongType { expected } => write!(f, "expected {}", expected),
+            ViolationKind::BelowMin { min } => write!(f, "value is less than {}", min),
+            ViolationKind::AboveMax { max } => write!(f, "value is greater than {}", max),
+            ViolationKind::TooShort { min_length } => {
+                write!(f, "value is shorter than {} characters", min_length)
+            }
+            ViolationKind::TooLong { max_length } => {
+                write!(f, "value is longer than {} characters", max_length)
+            }
+            ViolationKind::NoMatch { ref regex } => write!(f, "value does not match `{}`", regex),
+            ViolationKind::InvalidRegex { ref regex } => write!(f, "invalid regular expression `{}`", regex),
+            ViolationKind::Unchecked { ref regex } => {
+                write!(f, "`{}` was not checked without the `regex` feature", regex)
+            }
+            ViolationKind::NotAnOption { ref value } => write!(f, "`{}` is not one of the options", value),
+            ViolationKind::TooFewItems { min_items } => {
+                write!(f, "fewer than {} values are selected", min_items)
+            }
+            ViolationKind::TooManyItems { max_items } => {
+                write!(f, "more than {} values are selected", max_items)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at `{}`", self.kind, self.path)
+    }
+}
+
+/// The text of a string, number or boolean value
+fn text(value: &Json) -> Option<String> {
+    match *value {
+        Json::String(ref value) => Some(value.clone()),
+        Json::I64(_) | Json::U64(_) | Json::F64(_) | Json::Boolean(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Checks a value against a regular expression, which must match all of it
+/// as the HTML `pattern` attribute does
+#[cfg(feature = "regex")]
+fn check_regex(pattern: &str, text: &str) -> Option<ViolationKind> {
+    match ::regex::Regex::new(&format!("^(?:{})$", pattern)) {
+        Ok(ref regex) if regex.is_match(text) => None,
+        Ok(_) => Some(ViolationKind::NoMatch { regex: pattern.to_string() }),
+        Err(_) => Some(ViolationKind::InvalidRegex { regex: pattern.to_string() }),
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+fn check_regex(pattern: &str, _text: &str) -> Option<ViolationKind> {
+    Some(ViolationKind::Unchecked { regex: pattern.to_string() })
+}
+
+fn wrong_type(path: String, expected: &'static str, found: &Json) -> HalError {
+    HalError::WrongType { path, expected, found: json_type(found) }
+}
+
+fn optional_bool_at(object: &Object, path: &str, field: &str) -> Result<Option<bool>, HalError> {
+    match object.get(field) {
+        Some(&Json::Boolean(value)) => Ok(Some(value)),
+        Some(other) => Err(wrong_type(pointer(path, field), "boolean", other)),
+        None => Ok(None),
+    }
+}
+
+fn optional_f64_at(object: &Object, path: &str, field: &str) -> Result<Option<f64>, HalError> {
+    match object.get(field) {
+        Some(value) => match value.as_f64() {
+            Some(value) => Ok(Some(value)),
+            None => Err(wrong_type(pointer(path, field), "number", value)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn optional_u64_at(object: &Object, path: &str, field: &str) -> Result<Option<u64>, HalError> {
+    match object.get(field) {
+        Some(value) => match value.as_u64() {
+            Some(value) => Ok(Some(value)),
+            None => Err(wrong_type(pointer(path, field), "non-negative integer", value)),
+        },
+        None => Ok(None),
+    }
+}
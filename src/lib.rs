@@ -65,13 +65,51 @@
 //! ```
 
 extern crate rustc_serialize as serialize;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "derive")]
+extern crate hal_derive;
 
+pub mod curie;
+pub mod error;
+pub mod forms;
 pub mod state;
 pub mod link;
+pub mod lint;
+pub mod rel;
 pub mod resource;
+pub mod streaming;
+pub mod uri_template;
+pub mod view;
+pub mod writer;
+#[cfg(feature = "serde")]
+mod serde_impl;
 
+use error::HalError;
 use resource::Resource;
 
+#[cfg(feature = "derive")]
+pub use hal_derive::{FromHal, ToHal};
+
 pub trait ToHal {
     fn to_hal(self) -> Resource;
 }
+
+impl ToHal for Resource {
+    fn to_hal(self) -> Resource {
+        self
+    }
+}
+
+/// The inverse of `ToHal`, reading a value out of a Resource
+pub trait FromHal: Sized {
+    fn from_hal(resource: &Resource) -> Result<Self, HalError>;
+}
+
+impl FromHal for Resource {
+    fn from_hal(resource: &Resource) -> Result<Resource, HalError> {
+        Ok(resource.clone())
+    }
+}
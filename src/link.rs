@@ -1,23 +1,55 @@
 use std::collections::BTreeMap;
-use serialize::json::{ToJson, Json};
+use std::convert::TryFrom;
+use serialize::json::{ToJson, Json, Object};
+use serialize::{Encodable, Encoder};
+
+use error::{HalError, json_type, object_at, pointer};
+use state::HalObject;
+use uri_template::UriTemplate;
 
 /// A Hal Link object
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Link {
-    href: String,
-    templated: Option<bool>,
-    media_type: Option<String>,
-    deprecation: Option<String>,
-    name: Option<String>,
-    profile: Option<String>,
-    title: Option<String>,
-    hreflang: Option<String>,
+    pub(crate) href: String,
+    pub(crate) templated: Option<bool>,
+    pub(crate) media_type: Option<String>,
+    pub(crate) deprecation: Option<String>,
+    pub(crate) name: Option<String>,
+    pub(crate) profile: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) hreflang: Option<String>,
 }
 
 impl Link {
+    /// Create a link to the given URI
+    ///
+    /// An href containing URI Template (RFC 6570) expressions, such as
+    /// `/orders{?id}`, marks the link as templated. An href with malformed
+    /// braces is not treated as a template; use `Link::try_new` to reject it.
     pub fn new<S: Into<String>>(href: S) -> Link {
+        let mut link = Link::untemplated(href.into());
+        if let Ok(template) = UriTemplate::parse(&link.href) {
+            if template.has_expressions() {
+                link.templated = Some(true);
+            }
+        }
+        link
+    }
+
+    /// Create a link like `Link::new`, failing if the href is a malformed
+    /// URI Template
+    pub fn try_new<S: Into<String>>(href: S) -> Result<Link, HalError> {
+        let mut link = Link::untemplated(href.into());
+        if UriTemplate::parse(&link.href)?.has_expressions() {
+            link.templated = Some(true);
+        }
+        Ok(link)
+    }
+
+    /// Create a link without looking for template expressions in the href
+    fn untemplated(href: String) -> Link {
         Link {
-            href: href.into(),
+            href,
             templated: None,
             media_type: None,
             deprecation: None,
@@ -29,49 +61,109 @@ impl Link {
     }
 
     /// Convert a json object into a Link
+    ///
+    /// Panics if the object is not a valid Hal link. Use `Link::parse` to
+    /// handle malformed input.
+    #[deprecated(note = "use `Link::parse`, which reports where a link is malformed")]
     pub fn from_json(json: &Json) -> Link {
-        let ref url = json["href"];
+        match Link::parse(json) {
+            Ok(link) => link,
+            Err(e) => panic!("invalid Hal link: {}", e),
+        }
+    }
 
-        let mut link = Link::new(url.as_string().unwrap());
+    /// Convert a json object into a Link, reporting any member that is
+    /// missing or has the wrong type
+    pub fn parse(json: &Json) -> Result<Link, HalError> {
+        Link::parse_at(json, "")
+    }
 
-        // todo: we use these same hard coded strings to conver to json
-        // todo: make this a macro
-        if json.search("templated").is_some() {
-            let value = json.search("templated").unwrap();
-            link.templated(value.as_boolean().unwrap());
-        }
+    pub(crate) fn parse_at(json: &Json, path: &str) -> Result<Link, HalError> {
+        let object = object_at(json, path)?;
 
-        if json.search("type").is_some() {
-            let value = json.search("type").unwrap();
-            link.media_type(value.as_string().unwrap());
-        }
+        let href = match object.get("href") {
+            Some(value) => string_at(value, path, "href")?,
+            None => {
+                return Err(HalError::MissingField {
+                    path: path.to_string(),
+                    field: "href".to_string(),
+                })
+            }
+        };
 
-        if json.search("deprecation").is_some() {
-            let value = json.search("deprecation").unwrap();
-            link.deprecation(value.as_string().unwrap());
-        }
+        // The templated member is taken as given so that documents round trip
+        let mut link = Link::untemplated(href);
 
-        if json.search("name").is_some() {
-            let value = json.search("name").unwrap();
-            link.name(value.as_string().unwrap());
+        if let Some(value) = object.get("templated") {
+            match *value {
+                Json::Boolean(templated) => link.templated = Some(templated),
+                ref other => {
+                    return Err(HalError::WrongType {
+                        path: pointer(path, "templated"),
+                        expected: "boolean",
+                        found: json_type(other),
+                    })
+                }
+            }
         }
 
-        if json.search("title").is_some() {
-            let value = json.search("title").unwrap();
-            link.title(value.as_string().unwrap());
-        }
+        link.media_type = optional_string_at(object, path, "type")?;
+        link.deprecation = optional_string_at(object, path, "deprecation")?;
+        link.name = optional_string_at(object, path, "name")?;
+        link.profile = optional_string_at(object, path, "profile")?;
+        link.title = optional_string_at(object, path, "title")?;
+        link.hreflang = optional_string_at(object, path, "hreflang")?;
 
-        if json.search("profile").is_some() {
-            let value = json.search("profile").unwrap();
-            link.profile(value.as_string().unwrap());
-        }
+        Ok(link)
+    }
 
-        if json.search("hreflang").is_some() {
-            let value = json.search("hreflang").unwrap();
-            link.hreflang(value.as_string().unwrap());
-        }
+    /// Get the target URI (or URI Template) of the link
+    pub fn get_href(&self) -> &str {
+        &self.href
+    }
 
-        link
+    pub fn get_templated(&self) -> Option<bool> {
+        self.templated
+    }
+
+    pub fn get_media_type(&self) -> Option<&str> {
+        self.media_type.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_deprecation(&self) -> Option<&str> {
+        self.deprecation.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_ref().map(|s| &s[..])
+    }
+
+    pub fn get_hreflang(&self) -> Option<&str> {
+        self.hreflang.as_ref().map(|s| &s[..])
+    }
+
+    /// Expand the href as a URI Template (RFC 6570) into a concrete link
+    ///
+    /// The returned link keeps every other attribute but is no longer
+    /// templated.
+    pub fn expand(&self, vars: &HalObject) -> Result<Link, HalError> {
+        let mut link = self.clone();
+        link.href = self.expand_href(vars)?;
+        link.templated = None;
+        Ok(link)
+    }
+
+    /// Expand the href as a URI Template (RFC 6570)
+    pub fn expand_href(&self, vars: &HalObject) -> Result<String, HalError> {
+        UriTemplate::parse(&self.href).map(|template| template.expand(vars))
     }
 
     pub fn templated(&mut self, is_template: bool) -> &mut Link {
@@ -158,3 +250,90 @@ impl ToJson for Link {
         Json::Object(link)
     }
 }
+
+/// Writes the same json as `to_json` without building it first
+impl Encodable for Link {
+    fn encode<E: Encoder>(&self, e: &mut E) -> Result<(), E::Error> {
+        // The string members that sort before and after `templated`
+        let before = [("deprecation", self.deprecation.as_ref()),
+                      ("href", Some(&self.href)),
+                      ("hreflang", self.hreflang.as_ref()),
+                      ("name", self.name.as_ref()),
+                      ("profile", self.profile.as_ref())];
+        let after = [("title", self.title.as_ref()), ("type", self.media_type.as_ref())];
+
+        let strings = before.iter().chain(after.iter()).filter(|&&(_, value)| value.is_some()).count();
+        e.emit_map(strings + self.templated.iter().count(), |e| {
+            let mut i = 0;
+            encode_strings(e, &before, &mut i)?;
+            if let Some(templated) = self.templated {
+                e.emit_map_elt_key(i, |e| e.emit_str("templated"))?;
+                e.emit_map_elt_val(i, |e| e.emit_bool(templated))?;
+                i += 1;
+            }
+            encode_strings(e, &after, &mut i)
+        })
+    }
+}
+
+/// Writes the string members that are present, counting them with `i`
+fn encode_strings<E: Encoder>(e: &mut E, members: &[(&str, Option<&String>)], i: &mut usize) -> Result<(), E::Error> {
+    for &(key, value) in members {
+        if let Some(value) = value {
+            e.emit_map_elt_key(*i, |e| e.emit_str(key))?;
+            e.emit_map_elt_val(*i, |e| e.emit_str(value))?;
+            *i += 1;
+        }
+    }
+    Ok(())
+}
+
+impl From<String> for Link {
+    fn from(href: String) -> Link {
+        Link::new(href)
+    }
+}
+
+impl<'a> From<&'a str> for Link {
+    fn from(href: &'a str) -> Link {
+        Link::new(href)
+    }
+}
+
+impl<'a> From<&'a Link> for Link {
+    fn from(link: &'a Link) -> Link {
+        link.clone()
+    }
+}
+
+impl<'a> From<&'a mut Link> for Link {
+    fn from(link: &'a mut Link) -> Link {
+        link.clone()
+    }
+}
+
+impl TryFrom<Json> for Link {
+    type Error = HalError;
+
+    fn try_from(json: Json) -> Result<Link, HalError> {
+        Link::parse(&json)
+    }
+}
+
+pub(crate) fn string_at(value: &Json, path: &str, field: &str) -> Result<String, HalError> {
+    match *value {
+        Json::String(ref s) => Ok(s.clone()),
+        ref other => Err(HalError::WrongType {
+            path: pointer(path, field),
+            expected: "string",
+            found: json_type(other),
+        }),
+    }
+}
+
+pub(crate) fn optional_string_at(object: &Object, path: &str, field: &str) -> Result<Option<String>, HalError> {
+    match object.get(field) {
+        Some(value) => string_at(value, path, field).map(Some),
+        None => Ok(None),
+    }
+}
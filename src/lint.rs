@@ -0,0 +1,272 @@
+//! Checks a Hal document against the Hal specification.
+//!
+//! The checks find documents that are well formed but break or bend the
+//! rules of the specification, such as a resource without a `self` link or
+//! a link marked as templated whose href is not a URI Template. Every
+//! diagnostic carries the JSON Pointer of the value it refers to.
+
+use std::fmt;
+use serialize::json::Json;
+
+use curie::CurieIssueKind;
+use error::pointer;
+use link::Link;
+use rel;
+use resource::Resource;
+use uri_template::UriTemplate;
+
+/// How serious a diagnostic is
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Severity {
+    /// The document is fine but could be clearer
+    Info,
+    /// The document goes against a recommendation of the specification
+    Warning,
+    /// The document is broken
+    Error,
+}
+
+/// The check a diagnostic comes from
+#[derive(Clone, PartialEq, Debug)]
+pub enum Rule {
+    /// The document is not a valid Hal document at all
+    Malformed,
+    /// A resource has no `self` link
+    MissingSelf,
+    /// A link has an empty href
+    EmptyHref,
+    /// A link is marked as templated but its href has no expressions
+    TemplatedWithoutExpressions,
+    /// A link href has URI Template expressions but is not marked as templated
+    NotMarkedTemplated,
+    /// A templated link href is not a valid URI Template
+    InvalidTemplate,
+    /// A link deprecation is not a URL
+    DeprecationNotUrl,
+    /// Two links of the same rel share a name
+    DuplicateLinkName,
+    /// A state key starts with an underscore, which Hal uses for its own members
+    UnderscoreStateKey,
+    /// The `curies` rel is a single object instead of an array
+    CuriesNotArray,
+    /// A problem with the curies of the document
+    Curie(CurieIssueKind),
+    /// A rel is not registered with IANA, a curie or a URI
+    UnregisteredRel,
+}
+
+impl Rule {
+    pub fn severity(&self) -> Severity {
+        match *self {
+            Rule::Malformed |
+            Rule::EmptyHref |
+            Rule::InvalidTemplate |
+            Rule::Curie(CurieIssueKind::MissingName) |
+            Rule::Curie(CurieIssueKind::MissingRelVariable) => Severity::Error,
+            Rule::UnregisteredRel => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// A problem found in a Hal document
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    /// The JSON Pointer of the value the problem is with
+    pub path: String,
+    pub severity: Severity,
+    pub rule: Rule,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new<S: Into<String>>(path: String, rule: Rule, message: S) -> Diagnostic {
+        Diagnostic {
+            path,
+            severity: rule.severity(),
+            rule,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {} at `{}`", severity, self.message, self.path)
+    }
+}
+
+/// Checks a resource and every resource embedded in it
+///
+/// Links and embedded resources are located as `Resource::to_json` would
+/// write them. The diagnostics are sorted by path.
+pub fn check(resource: &Resource) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_at(resource, "", &mut diagnostics);
+
+    for issue in resource.validate_curies() {
+        let message = issue.kind.to_string();
+        diagnostics.push(Diagnostic::new(issue.path, Rule::Curie(issue.kind), message));
+    }
+
+    for path in rel::unregistered(resource) {
+        diagnostics.push(Diagnostic::new(path, Rule::UnregisteredRel, "rel is not registered, a curie or a URI"));
+    }
+
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    diagnostics
+}
+
+/// Checks a json document
+///
+/// Besides the checks of `check`, this finds problems that only show in the
+/// json, such as a `curies` rel written as a single object. A document that
+/// cannot be read as a Hal resource at all gives a single `Malformed`
+/// diagnostic.
+pub fn check_json(json: &Json) -> Vec<Diagnostic> {
+    let resource = match Resource::parse(json) {
+        Ok(resource) => resource,
+        Err(e) => {
+            let path = e.path().to_string();
+            return vec![Diagnostic::new(path, Rule::Malformed, e.to_string())];
+        }
+    };
+
+    let mut diagnostics = check(&resource);
+    check_json_at(json, "", &mut diagnostics);
+    diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+    diagnostics
+}
+
+fn check_at(resource: &Resource, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if resource.self_link().is_none() {
+        diagnostics.push(Diagnostic::new(path.to_string(), Rule::MissingSelf, "resource has no self link"));
+    }
+
+    for (key, _) in resource.state_entries() {
+        if key.starts_with('_') {
+            diagnostics.push(Diagnostic::new(pointer(path, key),
+                                             Rule::UnderscoreStateKey,
+                                             "state key starts with an underscore"));
+        }
+    }
+
+    let links_path = pointer(path, "_links");
+    for (rel, links) in resource.links() {
+        let rel_path = pointer(&links_path, rel);
+        let as_array = resource.links_are_array(rel);
+        let mut names = Vec::new();
+
+        for (i, link) in links.iter().enumerate() {
+            let link_path = if as_array {
+                pointer(&rel_path, &i.to_string())
+            } else {
+                rel_path.clone()
+            };
+            check_link(link, &link_path, diagnostics);
+
+            if let Some(name) = link.get_name() {
+                if names.contains(&name) {
+                    diagnostics.push(Diagnostic::new(pointer(&link_path, "name"),
+                                                     Rule::DuplicateLinkName,
+                                                     format!("another `{}` link is named `{}`", rel, name)));
+                }
+                names.push(name);
+            }
+        }
+    }
+
+    let embedded_path = pointer(path, "_embedded");
+    for (rel, resources) in resource.embedded() {
+        let rel_path = pointer(&embedded_path, rel);
+        for (i, embedded) in resources.iter().enumerate() {
+            check_at(embedded, &resource.embedded_pointer(&rel_path, rel, i), diagnostics);
+        }
+    }
+}
+
+fn check_link(link: &Link, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let href_path = pointer(path, "href");
+
+    if link.get_href().is_empty() {
+        diagnostics.push(Diagnostic::new(href_path.clone(), Rule::EmptyHref, "link href is empty"));
+    }
+
+    let templated = link.get_templated() == Some(true);
+    match UriTemplate::parse(link.get_href()) {
+        Ok(ref template) if templated && !template.has_expressions() => {
+            diagnostics.push(Diagnostic::new(pointer(path, "templated"),
+                                             Rule::TemplatedWithoutExpressions,
+                                             "link is templated but its href has no expressions"));
+        }
+        Ok(ref template) if !templated && template.has_expressions() => {
+            diagnostics.push(Diagnostic::new(href_path.clone(),
+                                             Rule::NotMarkedTemplated,
+                                             "link href is a URI Template but the link is not templated"));
+        }
+        Err(ref e) if templated => {
+            diagnostics.push(Diagnostic::new(href_path.clone(), Rule::InvalidTemplate, e.to_string()));
+        }
+        _ => {}
+    }
+
+    if let Some(deprecation) = link.get_deprecation() {
+        if !is_url(deprecation) {
+            diagnostics.push(Diagnostic::new(pointer(path, "deprecation"),
+                                             Rule::DeprecationNotUrl,
+                                             "link deprecation is not a URL"));
+        }
+    }
+}
+
+/// Checks what only the json shows, such as which rels are arrays
+fn check_json_at(json: &Json, path: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let object = match *json {
+        Json::Object(ref object) => object,
+        _ => return,
+    };
+
+    if let Some(Json::Object(links)) = object.get("_links") {
+        if let Some(&Json::Object(_)) = links.get("curies") {
+            diagnostics.push(Diagnostic::new(pointer(&pointer(path, "_links"), "curies"),
+                                             Rule::CuriesNotArray,
+                                             "curies is not an array"));
+        }
+    }
+
+    if let Some(Json::Object(embedded)) = object.get("_embedded") {
+        let embedded_path = pointer(path, "_embedded");
+        for (rel, value) in embedded.iter() {
+            let rel_path = pointer(&embedded_path, rel);
+            match *value {
+                Json::Array(ref values) => {
+                    for (i, value) in values.iter().enumerate() {
+                        check_json_at(value, &pointer(&rel_path, &i.to_string()), diagnostics);
+                    }
+                }
+                ref value => check_json_at(value, &rel_path, diagnostics),
+            }
+        }
+    }
+}
+
+/// Whether a value is an absolute URL or an absolute path
+fn is_url(value: &str) -> bool {
+    if value.starts_with('/') {
+        return true;
+    }
+    match value.find(':') {
+        Some(colon) => {
+            let scheme = &value[..colon];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+            scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.') &&
+            colon + 1 < value.len()
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,236 @@
+//! Link relation types.
+//!
+//! A rel is one of the relation types registered with IANA, such as `next`
+//! or `item`, a curie such as `ea:order`, or an extension relation type
+//! written as a full URI such as `http://example.com/rels/order`. Anything
+//! else is most likely a typo.
+//!
+//! `Rel` converts into `String`, so it can be passed anywhere a rel is taken:
+//!
+//! ```
+//! use hal::link::Link;
+//! use hal::rel::Rel;
+//! use hal::resource::Resource;
+//!
+//! let mut hal = Resource::with_self("/orders");
+//! hal.add_link(Rel::NEXT, &Link::new("/orders?page=2"));
+//!
+//! assert!(hal.get_link(&Rel::NEXT).is_some());
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+use curie;
+use error::pointer;
+use resource::Resource;
+
+/// The link relation types in the IANA registry, sorted
+pub const REGISTERED: &[&str] = &[
+    "about", "acl", "alternate", "amphtml", "api-catalog", "appendix",
+    "apple-touch-icon", "apple-touch-startup-image", "archives", "author",
+    "blocked-by", "bookmark", "c2pa-manifest", "canonical", "chapter",
+    "cite-as", "collection", "compression-dictionary", "contents",
+    "convertedfrom", "copyright", "create-form", "current", "deprecation",
+    "describedby", "describes", "disclosure", "dns-prefetch", "duplicate",
+    "edit", "edit-form", "edit-media", "enclosure", "external", "first",
+    "geofeed", "glossary", "help", "hosts", "hub", "ice-server", "icon",
+    "index", "intervalafter", "intervalbefore", "intervalcontains",
+    "intervaldisjoint", "intervalduring", "intervalequals",
+    "intervalfinishedby", "intervalfinishes", "intervalin", "intervalmeets",
+    "intervalmetby", "intervaloverlappedby", "intervaloverlaps",
+    "intervalstartedby", "intervalstarts", "item", "last", "latest-version",
+    "license", "linkset", "lrdd", "manifest", "mask-icon", "me", "media-feed",
+    "monitor", "monitor-group", "next", "next-archive", "nofollow",
+    "noopener", "noreferrer", "opener", "openid2.local_id",
+    "openid2.provider", "original", "p3pv1", "payment", "pingback",
+    "preconnect", "predecessor-version", "prefetch", "preload", "prerender",
+    "prev", "prev-archive", "preview", "previous", "privacy-policy",
+    "profile", "publication", "related", "replies", "restconf", "ruleinput",
+    "search", "section", "self", "service", "service-desc", "service-doc",
+    "service-meta", "sip-trunking-capability", "sponsored", "start",
+    "status", "stylesheet", "subsection", "successor-version", "sunset",
+    "tag", "terms-of-service", "timegate", "timemap", "type", "ugc", "up",
+    "version-history", "via", "webmention", "working-copy",
+    "working-copy-of",
+];
+
+/// A link relation type
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Rel(Cow<'static, str>);
+
+/// What kind of relation type a rel is
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RelKind {
+    /// A relation type in the IANA registry
+    Registered,
+    /// A compact URI such as `ea:order`
+    Curie,
+    /// An extension relation type written as a full URI
+    Extension,
+    /// None of the above
+    Unregistered,
+}
+
+impl Rel {
+    pub const ABOUT: Rel = Rel(Cow::Borrowed("about"));
+    pub const ALTERNATE: Rel = Rel(Cow::Borrowed("alternate"));
+    pub const AUTHOR: Rel = Rel(Cow::Borrowed("author"));
+    pub const COLLECTION: Rel = Rel(Cow::Borrowed("collection"));
+    pub const CREATE_FORM: Rel = Rel(Cow::Borrowed("create-form"));
+    pub const CURRENT: Rel = Rel(Cow::Borrowed("current"));
+    pub const DESCRIBEDBY: Rel = Rel(Cow::Borrowed("describedby"));
+    pub const EDIT: Rel = Rel(Cow::Borrowed("edit"));
+    pub const EDIT_FORM: Rel = Rel(Cow::Borrowed("edit-form"));
+    pub const EDIT_MEDIA: Rel = Rel(Cow::Borrowed("edit-media"));
+    pub const ENCLOSURE: Rel = Rel(Cow::Borrowed("enclosure"));
+    pub const FIRST: Rel = Rel(Cow::Borrowed("first"));
+    pub const HELP: Rel = Rel(Cow::Borrowed("help"));
+    pub const INDEX: Rel = Rel(Cow::Borrowed("index"));
+    pub const ITEM: Rel = Rel(Cow::Borrowed("item"));
+    pub const LAST: Rel = Rel(Cow::Borrowed("last"));
+    pub const LATEST_VERSION: Rel = Rel(Cow::Borrowed("latest-version"));
+    pub const LICENSE: Rel = Rel(Cow::Borrowed("license"));
+    pub const NEXT: Rel = Rel(Cow::Borrowed("next"));
+    pub const PREV: Rel = Rel(Cow::Borrowed("prev"));
+    pub const PREVIOUS: Rel = Rel(Cow::Borrowed("previous"));
+    pub const PROFILE: Rel = Rel(Cow::Borrowed("profile"));
+    pub const RELATED: Rel = Rel(Cow::Borrowed("related"));
+    pub const SEARCH: Rel = Rel(Cow::Borrowed("search"));
+    pub const SELF: Rel = Rel(Cow::Borrowed("self"));
+    pub const START: Rel = Rel(Cow::Borrowed("start"));
+    pub const TYPE: Rel = Rel(Cow::Borrowed("type"));
+    pub const UP: Rel = Rel(Cow::Borrowed("up"));
+    pub const VIA: Rel = Rel(Cow::Borrowed("via"));
+
+    /// The rel Hal uses to declare curies
+    pub const CURIES: Rel = Rel(Cow::Borrowed("curies"));
+
+    pub fn new<S: Into<String>>(rel: S) -> Rel {
+        Rel(Cow::Owned(rel.into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn kind(&self) -> RelKind {
+        kind(&self.0)
+    }
+
+    pub fn is_registered(&self) -> bool {
+        self.kind() == RelKind::Registered
+    }
+
+    pub fn is_curie(&self) -> bool {
+        self.kind() == RelKind::Curie
+    }
+
+    /// Whether this is an extension relation type written as a full URI
+    pub fn is_uri(&self) -> bool {
+        self.kind() == RelKind::Extension
+    }
+}
+
+/// Classifies a rel
+///
+/// As in Hal, a URI without `//` after its scheme, such as `urn:isbn:123`,
+/// reads as a curie.
+pub fn kind(rel: &str) -> RelKind {
+    if REGISTERED.binary_search(&rel).is_ok() {
+        RelKind::Registered
+    } else if curie::split(rel).is_some() {
+        RelKind::Curie
+    } else if has_scheme(rel) {
+        RelKind::Extension
+    } else {
+        RelKind::Unregistered
+    }
+}
+
+fn has_scheme(rel: &str) -> bool {
+    match rel.find("://") {
+        Some(end) => {
+            let scheme = &rel[..end];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic()) &&
+            scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+/// Finds the link and embedded rels of a resource, and of every resource
+/// embedded in it, that are not registered, curies or URIs
+///
+/// Each rel is reported as the JSON Pointer of its `_links` or `_embedded`
+/// member. The `curies` rel is Hal's own and is never reported.
+pub fn unregistered(resource: &Resource) -> Vec<String> {
+    let mut paths = Vec::new();
+    unregistered_at(resource, "", &mut paths);
+    paths
+}
+
+fn unregistered_at(resource: &Resource, path: &str, paths: &mut Vec<String>) {
+    let links_path = pointer(path, "_links");
+    for rel in resource.link_rels() {
+        if rel != Rel::CURIES.as_str() && kind(rel) == RelKind::Unregistered {
+            paths.push(pointer(&links_path, rel));
+        }
+    }
+
+    let embedded_path = pointer(path, "_embedded");
+    for (rel, resources) in resource.embedded() {
+        let rel_path = pointer(&embedded_path, rel);
+        if kind(rel) == RelKind::Unregistered {
+            paths.push(rel_path.clone());
+        }
+        for (i, embedded) in resources.iter().enumerate() {
+            unregistered_at(embedded, &resource.embedded_pointer(&rel_path, rel, i), paths);
+        }
+    }
+}
+
+impl Deref for Rel {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Rel {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Rel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&'static str> for Rel {
+    fn from(rel: &'static str) -> Rel {
+        Rel(Cow::Borrowed(rel))
+    }
+}
+
+impl From<String> for Rel {
+    fn from(rel: String) -> Rel {
+        Rel(Cow::Owned(rel))
+    }
+}
+
+impl From<Rel> for String {
+    fn from(rel: Rel) -> String {
+        rel.0.into_owned()
+    }
+}
+
+impl<'a> From<&'a Rel> for String {
+    fn from(rel: &'a Rel) -> String {
+        rel.0.to_string()
+    }
+}
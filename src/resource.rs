@@ -1,24 +1,194 @@
+use std::borrow::Cow;
 use std::collections::btree_map::Entry::{Occupied, Vacant};
-use std::collections::BTreeMap;
-use serialize::json::{ToJson, Json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryFrom;
+use std::fmt;
+use serialize::json::{ToJson, Json, Object, Parser, ParserError, JsonEvent, StackElement};
 use serialize::json;
+use serialize::{Encodable, Encoder};
 
+use curie::{self, CurieIssue};
+use error::{HalError, object_at, pointer};
+use forms::Template;
 use link::Link;
-use state::{HalState, ToHalState};
+use streaming::{Lazy, StreamingResource};
+use rel;
+use state::{FromHalState, HalState, ToHalState};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde_impl::to_state_fields;
 
-#[derive(Clone, PartialEq, Debug)]
+/// Keys that Hal uses for its own members and that state may not use
+///
+/// `_templates` is only reserved on a resource that has HAL-FORMS templates.
+pub const RESERVED_KEYS: &[&str] = &["_links", "_embedded"];
+
+/// How `Resource::parse_with` reads a document
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParseOptions {
+    forms: bool,
+    preserve_order: bool,
+}
+
+impl ParseOptions {
+    /// Read plain Hal, sorting members as `Resource::parse` does
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    /// Read `_templates` as HAL-FORMS templates rather than as state
+    pub fn forms(&mut self, forms: bool) -> &mut ParseOptions {
+        self.forms = forms;
+        self
+    }
+
+    /// Keep state members, link rels and embedded rels in the order they
+    /// appear in the text; every resource in the document then preserves
+    /// order, see `Resource::preserve_order`
+    pub fn preserve_order(&mut self, preserve: bool) -> &mut ParseOptions {
+        self.preserve_order = preserve;
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Resource {
-    state: BTreeMap<String, HalState>,
-    links: BTreeMap<String, Vec<Link>>,
-    resources: BTreeMap<String, Vec<Resource>>,
+    pub(crate) state: BTreeMap<String, HalState>,
+    pub(crate) links: BTreeMap<String, Vec<Link>>,
+    pub(crate) resources: BTreeMap<String, Vec<Resource>>,
+    templates: BTreeMap<String, Template>,
+    compact_rels: bool,
+    array_rels: BTreeSet<String>,
+    link_shapes: BTreeMap<String, bool>,
+    single_embedded: BTreeSet<String>,
+    preserve_order: bool,
+    order: Order,
+}
+
+/// The order in which top level members, link rels and embedded rels were
+/// first added; `_links`, `_embedded` and `_templates` are listed among the
+/// members
+#[derive(Clone, Default, Debug)]
+struct Order {
+    members: Vec<String>,
+    link_rels: Vec<String>,
+    embedded_rels: Vec<String>,
+}
+
+/// Records a key the first time it is added
+fn remember(order: &mut Vec<String>, key: &str) {
+    if !order.iter().any(|k| k == key) {
+        order.push(key.to_string());
+    }
+}
+
+/// Iterates over a map in the order its keys were first added, or in key order
+fn entries<'a, V>(map: &'a BTreeMap<String, V>,
+                  order: &'a [String],
+                  ordered: bool)
+                  -> Box<dyn Iterator<Item = (&'a String, &'a V)> + 'a> {
+    if ordered {
+        Box::new(order.iter().filter_map(move |key| map.get_key_value(key)))
+    } else {
+        Box::new(map.iter())
+    }
+}
+
+/// A top level member of a Hal document
+pub(crate) enum Member<'a> {
+    Links,
+    Embedded,
+    Templates,
+    State(&'a HalState),
+}
+
+/// The order in which the keys of each object appear in json text, by the
+/// JSON Pointer of the object
+struct DocumentOrder(BTreeMap<String, Vec<String>>);
+
+impl DocumentOrder {
+    fn read(text: &str) -> Result<DocumentOrder, HalError> {
+        let mut keys = BTreeMap::new();
+        let mut parser = Parser::new(text.chars());
+
+        while let Some(event) = parser.next() {
+            match event {
+                JsonEvent::Error(e) => return Err(syntax_error(e)),
+                JsonEvent::ObjectEnd | JsonEvent::ArrayEnd => continue,
+                _ => {}
+            }
+
+            let stack = parser.stack();
+            if let Some(StackElement::Key(key)) = stack.top() {
+                let mut path = String::new();
+                for i in 0..stack.len() - 1 {
+                    path = match stack.get(i) {
+                        StackElement::Key(key) => pointer(&path, key),
+                        StackElement::Index(index) => pointer(&path, &index.to_string()),
+                    };
+                }
+
+                let object_keys: &mut Vec<String> = keys.entry(path).or_default();
+                if !object_keys.iter().any(|k| k == key) {
+                    object_keys.push(key.to_string());
+                }
+            }
+        }
+
+        Ok(DocumentOrder(keys))
+    }
+
+    /// The members of the object at a path, in document order when it is known
+    fn members<'a>(order: Option<&DocumentOrder>, object: &'a Object, path: &str) -> Vec<(&'a String, &'a Json)> {
+        match order.and_then(|order| order.0.get(path)) {
+            Some(keys) => keys.iter().filter_map(|key| object.get_key_value(key)).collect(),
+            None => object.iter().collect(),
+        }
+    }
+}
+
+pub(crate) fn syntax_error(e: ParserError) -> HalError {
+    match e {
+        ParserError::SyntaxError(code, line, column) => {
+            HalError::Syntax { line, column, reason: json::error_str(code) }
+        }
+        ParserError::IoError(e) => HalError::Serialize(e.to_string()),
+    }
+}
+
+/// Resources are equal when they have the same content and are written the
+/// same way, except that whether a single link is written as an array does
+/// not count; the order things were added in only counts when both preserve it
+impl PartialEq for Resource {
+    fn eq(&self, other: &Resource) -> bool {
+        self.state == other.state &&
+        self.links == other.links &&
+        self.resources == other.resources &&
+        self.templates == other.templates &&
+        self.compact_rels == other.compact_rels &&
+        self.single_embedded == other.single_embedded &&
+        self.preserve_order == other.preserve_order &&
+        (!self.preserve_order || self.members().iter().map(|m| m.0).eq(other.members().iter().map(|m| m.0)) &&
+                                 self.link_rels().eq(other.link_rels()) &&
+                                 self.embedded_rels().eq(other.embedded_rels()))
+    }
 }
 
 impl Resource {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Resource {
         Resource {
             state: BTreeMap::new(),
             links: BTreeMap::new(),
             resources: BTreeMap::new(),
+            templates: BTreeMap::new(),
+            compact_rels: false,
+            array_rels: BTreeSet::new(),
+            link_shapes: BTreeMap::new(),
+            single_embedded: BTreeSet::new(),
+            preserve_order: false,
+            order: Order::default(),
         }
     }
 
@@ -26,94 +196,742 @@ impl Resource {
         where S: Into<String>
     {
 
-        let link = Link::new(uri);
         let mut resource = Resource::new();
-        resource.add_link("self", &link);
+        resource.add_link_owned("self", Link::new(uri));
         resource
     }
 
-    /// This feature is still experimental.
+    /// Convert a json object into a Resource
+    ///
+    /// Returns an empty resource if the json is not a valid Hal document.
+    #[deprecated(note = "use `Resource::parse`, which reports where a document is malformed")]
     pub fn from_json(json: Json) -> Resource {
+        Resource::parse(&json).unwrap_or_else(|_| Resource::new())
+    }
+
+    /// Convert a json object into a Resource, reporting where the document
+    /// is malformed
+    ///
+    /// This reads plain Hal, where a `_templates` member is state; use
+    /// `Resource::parse_with` to read HAL-FORMS templates or keep the order
+    /// of the text.
+    pub fn parse(json: &Json) -> Result<Resource, HalError> {
+        Resource::parse_at(json, "", None, false)
+    }
+
+    /// Parse json text into a Resource as the options say
+    ///
+    /// The text is needed rather than a `Json` value, whose objects are
+    /// sorted maps, to keep the order it was written in.
+    pub fn parse_with(text: &str, options: &ParseOptions) -> Result<Resource, HalError> {
+        let json = Json::from_str(text).map_err(syntax_error)?;
+        let order = if options.preserve_order {
+            Some(DocumentOrder::read(text)?)
+        } else {
+            None
+        };
+        Resource::parse_at(&json, "", order.as_ref(), options.forms)
+    }
+
+    /// Convert a json object into a Resource as the options say, except
+    /// that its order is already lost
+    #[cfg(feature = "serde")]
+    pub(crate) fn parse_json_with(json: &Json, options: &ParseOptions) -> Result<Resource, HalError> {
+        Resource::parse_at(json, "", None, options.forms)
+    }
+
+    fn parse_at(json: &Json, path: &str, order: Option<&DocumentOrder>, forms: bool) -> Result<Resource, HalError> {
+        let object = object_at(json, path)?;
+
         let mut resource = Resource::new();
+        resource.preserve_order = order.is_some();
+
+        for (key, value) in DocumentOrder::members(order, object, path) {
+            if key == "_links" {
+                let links_path = pointer(path, key);
+                let links = object_at(value, &links_path)?;
 
-        if json.is_object() {
-            let json = json.as_object().unwrap();
-            for (key, value) in json.iter() {
-                if key as &str == "_links" {
-                    let links = value.as_object().unwrap();
+                for (rel, value) in DocumentOrder::members(order, links, &links_path) {
+                    let rel_path = pointer(&links_path, rel);
+                    let as_array = match *value {
+                        Json::Array(ref values) => {
+                            // An empty array keeps its rel so that it round trips
+                            resource.link_rel(rel.clone());
+                            for (i, value) in values.iter().enumerate() {
+                                let link = Link::parse_at(value, &pointer(&rel_path, &i.to_string()))?;
+                                resource.add_link_owned(&rel[..], link);
+                            }
+                            true
+                        }
+                        ref value => {
+                            let link = Link::parse_at(value, &rel_path)?;
+                            resource.add_link_owned(&rel[..], link);
+                            false
+                        }
+                    };
 
-                    for (link_key, link_object) in links.iter() {
-                        let link = Link::from_json(&link_object.to_json());
-                        resource.add_link(&link_key[..], &link);
+                    // Remember a shape that would not otherwise round trip
+                    if resource.get_links(rel).len() == 1 && as_array != resource.is_array_rel(rel) {
+                        resource.links_as_array(&rel[..], as_array);
                     }
-                } else {
-                    resource.add_state(&key[..], value.clone());
                 }
+            } else if key == "_embedded" {
+                let embedded_path = pointer(path, key);
+                let embedded = object_at(value, &embedded_path)?;
+
+                for (rel, value) in DocumentOrder::members(order, embedded, &embedded_path) {
+                    let rel_path = pointer(&embedded_path, rel);
+                    match *value {
+                        Json::Array(ref values) => {
+                            // An empty array keeps its rel so that it round trips
+                            resource.embedded_rel(rel.clone());
+                            for (i, value) in values.iter().enumerate() {
+                                let item_path = pointer(&rel_path, &i.to_string());
+                                let embedded = Resource::parse_at(value, &item_path, order, forms)?;
+                                resource.add_resource_owned(&rel[..], embedded);
+                            }
+                        }
+                        ref value => {
+                            let embedded = Resource::parse_at(value, &rel_path, order, forms)?;
+                            resource.set_embedded_owned(&rel[..], embedded);
+                        }
+                    }
+                }
+            } else if key == "_templates" && forms {
+                let templates_path = pointer(path, key);
+                let templates = object_at(value, &templates_path)?;
+
+                for (name, value) in templates.iter() {
+                    let template = Template::parse_at(value, &pointer(&templates_path, name))?;
+                    resource.add_template(&name[..], template);
+                }
+            } else if key == "_templates" {
+                // Plain Hal does not reserve the key, so keep it as state
+                remember(&mut resource.order.members, key);
+                resource.state.insert(key.clone(), value.to_hal_state());
+            } else {
+                resource.add_state(&key[..], value.to_hal_state());
             }
         }
 
-        resource
+        Ok(resource)
+    }
+
+    /// Get the state member with the given key
+    pub fn get_state(&self, key: &str) -> Option<&HalState> {
+        self.state.get(key)
+    }
+
+    /// Get the state member with the given key converted into a value
+    ///
+    /// A missing member is an error unless `T` has a value for it, such as
+    /// `None` for an `Option`.
+    pub fn get_state_as<T: FromHalState>(&self, key: &str) -> Result<T, HalError> {
+        match self.state.get(key) {
+            Some(state) => T::from_hal_state(state).map_err(|e| e.nested_in(&pointer("", key))),
+            None => {
+                T::from_missing().ok_or_else(|| {
+                    HalError::MissingField {
+                        path: "".to_string(),
+                        field: key.to_string(),
+                    }
+                })
+            }
+        }
+    }
+
+    /// Iterate over the state members in key order, or in the order they
+    /// were added when this resource preserves order
+    pub fn state_entries<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a HalState)> + 'a {
+        entries(&self.state, &self.order.members, self.preserve_order).map(|(key, value)| (&key[..], value))
+    }
+
+    /// Get the first link with the given rel
+    pub fn get_link(&self, rel: &str) -> Option<&Link> {
+        self.get_links(rel).first()
+    }
+
+    /// Get all links with the given rel
+    pub fn get_links(&self, rel: &str) -> &[Link] {
+        self.links.get(&self.compacted_ref(rel)[..]).map(|links| &links[..]).unwrap_or(&[])
+    }
+
+    /// Get the link to this resource
+    pub fn self_link(&self) -> Option<&Link> {
+        self.get_link("self")
+    }
+
+    /// Iterate over the rels that have links
+    pub fn link_rels<'a>(&'a self) -> impl Iterator<Item = &'a str> + 'a {
+        self.links().map(|(rel, _)| rel)
+    }
+
+    /// Iterate over each rel along with its links
+    pub fn links<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a [Link])> + 'a {
+        entries(&self.links, &self.order.link_rels, self.preserve_order).map(|(rel, links)| (&rel[..], &links[..]))
+    }
+
+    /// Get the first embedded resource with the given rel
+    pub fn get_embedded_resource(&self, rel: &str) -> Option<&Resource> {
+        self.get_embedded(rel).first()
+    }
+
+    /// Get all embedded resources with the given rel
+    pub fn get_embedded(&self, rel: &str) -> &[Resource] {
+        self.resources.get(&self.compacted_ref(rel)[..]).map(|resources| &resources[..]).unwrap_or(&[])
+    }
+
+    /// Iterate over the rels that have embedded resources
+    pub fn embedded_rels<'a>(&'a self) -> impl Iterator<Item = &'a str> + 'a {
+        self.embedded().map(|(rel, _)| rel)
+    }
+
+    /// Iterate over each rel along with its embedded resources
+    pub fn embedded<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a [Resource])> + 'a {
+        entries(&self.resources, &self.order.embedded_rels, self.preserve_order)
+            .map(|(rel, resources)| (&rel[..], &resources[..]))
+    }
+
+    /// The embedded rels in the order they are written, each with its
+    /// resources, counting rels that only have lazy resources
+    pub(crate) fn embedded_members<'a>(&'a self, lazy: &Lazy) -> Vec<(&'a str, &'a [Resource])> {
+        let written = |rel: &&'a String| self.resources.contains_key(*rel) || lazy.contains_key(*rel);
+        let member = |rel: &'a String| (&rel[..], self.get_embedded(rel));
+
+        if self.preserve_order {
+            self.order.embedded_rels.iter().filter(written).map(member).collect()
+        } else {
+            // Lazy rels are remembered in the order whatever the setting
+            let rels: BTreeSet<&String> = self.resources.keys().chain(self.order.embedded_rels.iter()).filter(written).collect();
+            rels.into_iter().map(member).collect()
+        }
+    }
+
+    /// The top level members of this resource in the order they are written
+    pub(crate) fn members<'a>(&'a self) -> Vec<(&'a str, Member<'a>)> {
+        self.members_with(&Lazy::new())
+    }
+
+    /// The top level members in the order they are written, with `_embedded`
+    /// when only lazy resources are embedded
+    pub(crate) fn members_with<'a>(&'a self, lazy: &Lazy) -> Vec<(&'a str, Member<'a>)> {
+        let member = |key: &str| match key {
+            "_links" if !self.links.is_empty() => Some(Member::Links),
+            "_embedded" if !self.resources.is_empty() || !lazy.is_empty() => Some(Member::Embedded),
+            "_templates" if !self.templates.is_empty() => Some(Member::Templates),
+            _ => self.state.get(key).map(Member::State),
+        };
+
+        if self.preserve_order {
+            self.order.members.iter().filter_map(|key| member(key).map(|m| (&key[..], m))).collect()
+        } else {
+            let mut keys: Vec<&str> = self.state.keys().map(|key| &key[..]).collect();
+            keys.push("_links");
+            keys.push("_embedded");
+            keys.push("_templates");
+            keys.sort();
+            // `_templates` may also be state read from plain Hal
+            keys.dedup();
+            keys.into_iter().filter_map(|key| member(key).map(|m| (key, m))).collect()
+        }
     }
 
+    /// Add a state member
+    ///
+    /// Panics if the key is reserved, see `try_add_state`, as it would
+    /// clobber the links, embedded resources or templates. Use
+    /// `try_add_state` when the key is not known to be safe.
     pub fn add_state<S, V>(&mut self, key: S, value: V) -> &mut Resource
         where V: ToHalState,
               S: Into<String>
     {
-        self.state.insert(key.into(), value.to_hal_state());
+        if let Err(e) = self.try_add_state(key, value) {
+            panic!("{}", e);
+        }
         self
     }
 
+    /// Add a state member, failing if the key is one of `RESERVED_KEYS`, or
+    /// is `_templates` and this resource has templates
+    pub fn try_add_state<S, V>(&mut self, key: S, value: V) -> Result<&mut Resource, HalError>
+        where V: ToHalState,
+              S: Into<String>
+    {
+        let key = key.into();
+        if self.is_reserved(&key) {
+            return Err(HalError::ReservedKey(key));
+        }
+
+        remember(&mut self.order.members, &key);
+        self.state.insert(key, value.into_hal_state());
+        Ok(self)
+    }
+
+    /// Whether a state member may not use a key
+    fn is_reserved(&self, key: &str) -> bool {
+        RESERVED_KEYS.contains(&key) || (key == "_templates" && !self.templates.is_empty())
+    }
+
+    /// Create a Resource whose state is the top level fields of a
+    /// serializable struct or map
+    #[cfg(feature = "serde")]
+    pub fn from_serializable<T: ?Sized + Serialize>(value: &T) -> Result<Resource, HalError> {
+        let mut resource = Resource::new();
+        resource.add_state_from(value)?;
+        Ok(resource)
+    }
+
+    /// Add each top level field of a serializable struct or map as state
+    ///
+    /// Fails if the value does not serialize to an object or has a field
+    /// with a reserved key, as `try_add_state` does. The fields are added in the order
+    /// they are serialized, which for a struct is the order it declares them.
+    #[cfg(feature = "serde")]
+    pub fn add_state_from<T>(&mut self, value: &T) -> Result<&mut Resource, HalError>
+        where T: ?Sized + Serialize
+    {
+        let fields = to_state_fields(value)?;
+        if let Some(key) = fields.iter().map(|field| &field.0).find(|key| self.is_reserved(key)) {
+            return Err(HalError::ReservedKey(key.clone()));
+        }
+        for (key, value) in fields {
+            remember(&mut self.order.members, &key);
+            self.state.insert(key, value);
+        }
+        Ok(self)
+    }
+
+    /// Add a link, cloning it
     pub fn add_link<S>(&mut self, rel: S, link: &Link) -> &mut Resource
         where S: Into<String>
     {
-        match self.links.entry(rel.into()) {
+        self.add_link_owned(rel, link.clone())
+    }
+
+    /// Add a link, moving it in rather than cloning it
+    pub fn add_link_owned<S>(&mut self, rel: S, link: Link) -> &mut Resource
+        where S: Into<String>
+    {
+        let rel = self.compacted(rel.into());
+        self.link_rel(rel).push(link);
+        self
+    }
+
+    /// The links of a rel, which is added with none if it has no links yet
+    fn link_rel(&mut self, rel: String) -> &mut Vec<Link> {
+        remember(&mut self.order.members, "_links");
+        match self.links.entry(rel) {
             Vacant(entry) => {
-                let l = vec![link.clone()];
-                entry.insert(l);
+                remember(&mut self.order.link_rels, entry.key());
+                entry.insert(Vec::new())
             }
-            Occupied(entry) => {
-                let links = entry.into_mut();
-                links.push(link.clone());
+            Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Write the links of a rel as an array even when there is only one, or
+    /// as a single object when there is only one
+    ///
+    /// This overrides `is_array_rel` for this resource. Several links are
+    /// always written as an array.
+    pub fn links_as_array<S>(&mut self, rel: S, as_array: bool) -> &mut Resource
+        where S: Into<String>
+    {
+        let rel = self.compacted(rel.into());
+        self.link_shapes.insert(rel, as_array);
+        self
+    }
+
+    /// Write the links of a rel as an array even when there is only one, in
+    /// this resource and the resources embedded in it
+    ///
+    /// Resources embedded later inherit the rel too. Any of them can still
+    /// say otherwise with `links_as_array`.
+    pub fn add_array_rel<S>(&mut self, rel: S) -> &mut Resource
+        where S: Into<String>
+    {
+        let rel = rel.into();
+        for resources in self.resources.values_mut() {
+            for resource in resources {
+                resource.add_array_rel(&rel[..]);
             }
-        };
+        }
+        self.array_rels.insert(rel);
+        self
+    }
 
+    /// Undo `add_array_rel` for this resource and the resources embedded in it
+    pub fn remove_array_rel(&mut self, rel: &str) -> &mut Resource {
+        for resources in self.resources.values_mut() {
+            for resource in resources {
+                resource.remove_array_rel(rel);
+            }
+        }
+        self.array_rels.remove(rel);
         self
     }
 
+    /// Whether a single link of a rel is written as an array when this
+    /// resource does not say otherwise with `links_as_array`
+    ///
+    /// This is true for `curies` and for the rels added with `add_array_rel`.
+    pub fn is_array_rel(&self, rel: &str) -> bool {
+        rel == "curies" || self.array_rels.contains(rel)
+    }
+
+    /// Whether the links of a rel are written as an array, which is always
+    /// the case for none or several
+    pub(crate) fn links_are_array(&self, rel: &str) -> bool {
+        self.get_links(rel).len() != 1 ||
+        self.link_shapes.get(rel).cloned().unwrap_or_else(|| self.is_array_rel(rel))
+    }
+
+    /// Passes the array rels of this resource on to a resource embedded in it
+    pub(crate) fn inherit(&self, resource: &mut Resource) {
+        for rel in &self.array_rels {
+            resource.add_array_rel(&rel[..]);
+        }
+    }
+
     pub fn add_curie<S>(&mut self, name: S, href: S) -> &mut Resource
         where S: Into<String>
     {
         let mut link = Link::new(href);
         link.templated(true).name(name);
-        self.add_link("curies", &link)
+        self.add_link_owned("curies", link)
     }
 
+    /// Embed a copy of a resource in a to-many rel, which is written as an
+    /// array
     pub fn add_resource<S>(&mut self, rel: S, resource: &Resource) -> &mut Resource
         where S: Into<String>
     {
-        match self.resources.entry(rel.into()) {
+        self.add_resource_owned(rel, resource.clone())
+    }
+
+    /// Embed a resource in a to-many rel, moving it in rather than cloning it
+    pub fn add_resource_owned<S>(&mut self, rel: S, mut resource: Resource) -> &mut Resource
+        where S: Into<String>
+    {
+        self.inherit(&mut resource);
+        let rel = self.compacted(rel.into());
+        self.single_embedded.remove(&rel);
+        self.embedded_rel(rel).push(resource);
+        self
+    }
+
+    /// The resources embedded with a rel, which is added with none if it is
+    /// not embedded yet
+    fn embedded_rel(&mut self, rel: String) -> &mut Vec<Resource> {
+        remember(&mut self.order.members, "_embedded");
+        match self.resources.entry(rel) {
             Vacant(entry) => {
-                let r = vec![resource.clone()];
-                entry.insert(r);
+                remember(&mut self.order.embedded_rels, entry.key());
+                entry.insert(Vec::new())
             }
-            Occupied(entry) => {
-                let resources = entry.into_mut();
-                resources.push(resource.clone());
+            Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    /// Embed the one resource of a to-one rel, which is written as a single
+    /// object rather than an array
+    ///
+    /// This replaces any resources already embedded with the rel. The
+    /// resource is cloned; use `set_embedded_owned` to move it in.
+    pub fn set_embedded<S>(&mut self, rel: S, resource: &Resource) -> &mut Resource
+        where S: Into<String>
+    {
+        self.set_embedded_owned(rel, resource.clone())
+    }
+
+    /// Embed the one resource of a to-one rel, moving it in rather than
+    /// cloning it
+    pub fn set_embedded_owned<S>(&mut self, rel: S, mut resource: Resource) -> &mut Resource
+        where S: Into<String>
+    {
+        self.inherit(&mut resource);
+        let rel = self.compacted(rel.into());
+        self.single_embedded.insert(rel.clone());
+        remember(&mut self.order.members, "_embedded");
+        remember(&mut self.order.embedded_rels, &rel);
+        self.resources.insert(rel, vec![resource]);
+        self
+    }
+
+    /// Turn this resource into one that can also embed resources from
+    /// iterators, which are only consumed when it is written
+    pub fn into_streaming<'a>(self) -> StreamingResource<'a> {
+        StreamingResource::new(self)
+    }
+
+    /// Make a rel ready for lazy resources, returning it as it is written
+    pub(crate) fn embed_lazily(&mut self, rel: String) -> String {
+        let rel = self.compacted(rel);
+        self.single_embedded.remove(&rel);
+        remember(&mut self.order.members, "_embedded");
+        remember(&mut self.order.embedded_rels, &rel);
+        rel
+    }
+
+    /// Whether the resources embedded with a rel are written as an array
+    pub(crate) fn embedded_are_array(&self, rel: &str) -> bool {
+        !self.single_embedded.contains(rel) || self.get_embedded(rel).len() != 1
+    }
+
+    /// The JSON Pointer of the i-th resource embedded with a rel, as
+    /// `to_json` writes it, from the pointer of the rel
+    pub(crate) fn embedded_pointer(&self, rel_path: &str, rel: &str, i: usize) -> String {
+        if self.embedded_are_array(rel) {
+            pointer(rel_path, &i.to_string())
+        } else {
+            rel_path.to_string()
+        }
+    }
+
+    /// Add a HAL-FORMS template, replacing any template with the same key
+    ///
+    /// Templates are written under `_templates`; HAL-FORMS calls the
+    /// template of the main action of a resource `default`. The template is
+    /// moved in when given by value; a reference is cloned.
+    pub fn add_template<S, T>(&mut self, key: S, template: T) -> &mut Resource
+        where S: Into<String>,
+              T: Into<Template>
+    {
+        // Templates take the place of a `_templates` member read as state
+        self.state.remove("_templates");
+        remember(&mut self.order.members, "_templates");
+        self.templates.insert(key.into(), template.into());
+        self
+    }
+
+    /// Get the HAL-FORMS template with the given key
+    pub fn get_template(&self, key: &str) -> Option<&Template> {
+        self.templates.get(key)
+    }
+
+    /// Iterate over the HAL-FORMS templates in key order
+    pub fn templates<'a>(&'a self) -> impl Iterator<Item = (&'a str, &'a Template)> + 'a {
+        self.templates.iter().map(|(key, template)| (&key[..], template))
+    }
+
+    /// Remove a HAL-FORMS template, returning it
+    pub fn remove_template(&mut self, key: &str) -> Option<Template> {
+        let removed = self.templates.remove(key);
+        self.forget_removed();
+        removed
+    }
+
+    /// Keep state members, link rels and embedded rels in the order they were
+    /// first added rather than in key order
+    ///
+    /// The order is used by the accessors, by `Display` (and so by
+    /// `to_string`), by `Encodable` and `hal::writer`, and by `Serialize`,
+    /// with `_links`, `_embedded` and `_templates` placed where they were
+    /// first added. `to_json` cannot keep it as the objects of a `Json` value
+    /// are sorted maps, so write text with `to_string` rather than
+    /// `to_json().to_string()`. Each embedded resource has its own setting.
+    pub fn preserve_order(&mut self, preserve: bool) -> &mut Resource {
+        self.preserve_order = preserve;
+        self
+    }
+
+    /// Drops the keys that are no longer present from the recorded order
+    fn forget_removed(&mut self) {
+        let Resource { ref state, ref links, ref resources, ref templates, ref mut order, .. } = *self;
+        order.members.retain(|key| match &key[..] {
+            "_links" => !links.is_empty(),
+            "_embedded" => !resources.is_empty(),
+            "_templates" => !templates.is_empty() || state.contains_key("_templates"),
+            key => state.contains_key(key),
+        });
+        order.link_rels.retain(|rel| links.contains_key(rel));
+        order.embedded_rels.retain(|rel| resources.contains_key(rel));
+    }
+
+    /// Compact full URI rels with the declared curies as links and embedded
+    /// resources are added or set, and when they are looked up or removed
+    ///
+    /// Only curies declared before a rel is added are used to compact it.
+    pub fn compact_rels(&mut self, compact: bool) -> &mut Resource {
+        self.compact_rels = compact;
+        self
+    }
+
+    /// Get the curie declared with the given name
+    pub fn get_curie(&self, name: &str) -> Option<&Link> {
+        self.curies().iter().find(|curie| curie.get_name() == Some(name))
+    }
+
+    /// Expand a curie rel such as `ea:order` into the URI it stands for
+    ///
+    /// Returns `None` if the rel is not a curie or its prefix is not declared
+    /// on this resource.
+    pub fn expand_rel(&self, rel: &str) -> Option<String> {
+        let (prefix, reference) = curie::split(rel)?;
+        curie::expand(self.get_curie(prefix)?, reference)
+    }
+
+    /// Compact a URI into a curie rel using the curies declared on this
+    /// resource, if any of them matches
+    pub fn compact_rel(&self, uri: &str) -> Option<String> {
+        self.curies()
+            .iter()
+            .filter_map(|curie| {
+                let name = curie.get_name()?;
+                curie::compact(curie, uri).map(|reference| format!("{}:{}", name, reference))
+            })
+            .next()
+    }
+
+    /// Check that every curie rel is declared, and that the curies of this
+    /// resource and its embedded resources are well formed and consistent
+    pub fn validate_curies(&self) -> Vec<CurieIssue> {
+        curie::validate(self)
+    }
+
+    /// Find the rels of this resource and its embedded resources that are not
+    /// registered with IANA, curies or URIs, as JSON Pointers
+    pub fn unregistered_rels(&self) -> Vec<String> {
+        rel::unregistered(self)
+    }
+
+    fn compacted(&self, rel: String) -> String {
+        match self.compacted_ref(&rel) {
+            Cow::Owned(compact) => compact,
+            Cow::Borrowed(_) => rel,
+        }
+    }
+
+    /// The rel a link or embedded resource is kept under, as `compacted`
+    /// but without copying a rel that stays the same
+    fn compacted_ref<'r>(&self, rel: &'r str) -> Cow<'r, str> {
+        if self.compact_rels {
+            if let Some(compact) = self.compact_rel(rel) {
+                return Cow::Owned(compact);
             }
         }
+        Cow::Borrowed(rel)
+    }
+
+    /// The curies, which are never themselves compacted
+    fn curies(&self) -> &[Link] {
+        self.links.get("curies").map(|links| &links[..]).unwrap_or(&[])
+    }
+
+    /// Remove a state member, returning its value
+    pub fn remove_state(&mut self, key: &str) -> Option<HalState> {
+        let removed = self.state.remove(key);
+        self.forget_removed();
+        removed
+    }
 
+    /// Keep only the state members for which the predicate returns true
+    pub fn retain_state<F>(&mut self, mut f: F) -> &mut Resource
+        where F: FnMut(&str, &HalState) -> bool
+    {
+        self.state.retain(|key, value| f(key, value));
+        self.forget_removed();
+        self
+    }
+
+    /// Replace all links for a rel; an empty list removes the rel
+    pub fn set_links<S>(&mut self, rel: S, links: Vec<Link>) -> &mut Resource
+        where S: Into<String>
+    {
+        let rel = self.compacted(rel.into());
+        if links.is_empty() {
+            self.links.remove(&rel);
+            self.forget_removed();
+        } else {
+            remember(&mut self.order.members, "_links");
+            remember(&mut self.order.link_rels, &rel);
+            self.links.insert(rel, links);
+        }
+        self
+    }
+
+    /// Remove all links for a rel, returning them
+    pub fn remove_link(&mut self, rel: &str) -> Option<Vec<Link>> {
+        let rel = self.compacted(rel.to_string());
+        let removed = self.links.remove(&rel);
+        self.forget_removed();
+        removed
+    }
+
+    /// Keep only the links for which the predicate returns true
+    pub fn retain_links<F>(&mut self, mut f: F) -> &mut Resource
+        where F: FnMut(&str, &Link) -> bool
+    {
+        for (rel, links) in self.links.iter_mut() {
+            links.retain(|link| f(rel, link));
+        }
+        self.links.retain(|_, links| !links.is_empty());
+        self.forget_removed();
+        self
+    }
+
+    /// Remove the links for which the predicate returns true
+    pub fn remove_links_where<F>(&mut self, mut f: F) -> &mut Resource
+        where F: FnMut(&str, &Link) -> bool
+    {
+        self.retain_links(|rel, link| !f(rel, link))
+    }
+
+    /// Replace all embedded resources for a rel, which is then written as an
+    /// array; an empty list removes the rel
+    pub fn set_resources<S>(&mut self, rel: S, mut resources: Vec<Resource>) -> &mut Resource
+        where S: Into<String>
+    {
+        for resource in &mut resources {
+            self.inherit(resource);
+        }
+        let rel = self.compacted(rel.into());
+        self.single_embedded.remove(&rel);
+        if resources.is_empty() {
+            self.resources.remove(&rel);
+            self.forget_removed();
+        } else {
+            remember(&mut self.order.members, "_embedded");
+            remember(&mut self.order.embedded_rels, &rel);
+            self.resources.insert(rel, resources);
+        }
+        self
+    }
+
+    /// Remove all embedded resources for a rel, returning them
+    pub fn remove_embedded(&mut self, rel: &str) -> Option<Vec<Resource>> {
+        let rel = self.compacted(rel.to_string());
+        self.single_embedded.remove(&rel);
+        let removed = self.resources.remove(&rel);
+        self.forget_removed();
+        removed
+    }
+
+    /// Keep only the embedded resources for which the predicate returns true
+    pub fn retain_embedded<F>(&mut self, mut f: F) -> &mut Resource
+        where F: FnMut(&str, &Resource) -> bool
+    {
+        for (rel, resources) in self.resources.iter_mut() {
+            resources.retain(|resource| f(rel, resource));
+        }
+        self.resources.retain(|_, resources| !resources.is_empty());
+        let resources = &self.resources;
+        self.single_embedded.retain(|rel| resources.contains_key(rel));
+        self.forget_removed();
         self
     }
 }
 
+/// Builds the document as a `Json` value, whose objects are always sorted by
+/// key; see `Resource::preserve_order`
 impl ToJson for Resource {
     fn to_json(&self) -> json::Json {
         let mut hal = BTreeMap::new();
         let mut link_rels = BTreeMap::new();
 
-        if self.links.len() > 0 {
+        if !self.links.is_empty() {
             for (rel, links) in self.links.iter() {
-                if links.len() > 1 || (rel as &str == "curies") {
+                if self.links_are_array(rel) {
                     link_rels.insert(rel.clone(), (*links).to_json());
                 } else {
                     link_rels.insert(rel.clone(), links[0].to_json());
@@ -129,10 +947,146 @@ impl ToJson for Resource {
             hal.insert(k.clone().to_string(), v.to_json());
         }
 
-        if self.resources.len() > 0 {
-            hal.insert("_embedded".to_string(), self.resources.to_json());
+        if !self.resources.is_empty() {
+            let mut embedded_rels = BTreeMap::new();
+            for (rel, resources) in self.resources.iter() {
+                if self.embedded_are_array(rel) {
+                    embedded_rels.insert(rel.clone(), resources.to_json());
+                } else {
+                    embedded_rels.insert(rel.clone(), resources[0].to_json());
+                }
+            }
+
+            hal.insert("_embedded".to_string(), embedded_rels.to_json());
+        }
+
+        if !self.templates.is_empty() {
+            hal.insert("_templates".to_string(), self.templates.to_json());
         }
 
         json::Json::Object(hal)
     }
 }
+
+/// Writes the same json as `to_json`, except that a resource that preserves
+/// order is written in that order
+impl Encodable for Resource {
+    fn encode<E: Encoder>(&self, e: &mut E) -> Result<(), E::Error> {
+        self.encode_with(e, &mut Lazy::new())
+    }
+}
+
+/// Writes the document as compact json, or indented like `Json::pretty` with
+/// `{:#}`, in the order the resource preserves if it does
+///
+/// Unless the resource preserves order this is the same text as
+/// `to_json().to_string()`.
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let result = if f.alternate() {
+            self.encode(&mut json::Encoder::new_pretty(f))
+        } else {
+            self.encode(&mut json::Encoder::new(f))
+        };
+        result.map_err(|_| fmt::Error)
+    }
+}
+
+impl Resource {
+    /// Writes the document, taking the lazy resources of each rel as it is
+    /// written
+    pub(crate) fn encode_with<E: Encoder>(&self, e: &mut E, lazy: &mut Lazy) -> Result<(), E::Error> {
+        let members = self.members_with(lazy);
+        e.emit_map(members.len(), |e| {
+            for (i, &(key, ref member)) in members.iter().enumerate() {
+                e.emit_map_elt_key(i, |e| key.encode(e))?;
+                e.emit_map_elt_val(i, |e| match *member {
+                    Member::Links => {
+                        encode_rels(e, self.links().collect(), |rel| self.links_are_array(rel))
+                    }
+                    Member::Embedded => self.encode_embedded(e, lazy),
+                    Member::Templates => self.templates.encode(e),
+                    Member::State(state) => state.encode(e),
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Writes the `_embedded` object, consuming the lazy resources
+    fn encode_embedded<E: Encoder>(&self, e: &mut E, lazy: &mut Lazy) -> Result<(), E::Error> {
+        let rels = self.embedded_members(lazy);
+        e.emit_map(rels.len(), |e| {
+            for (i, &(rel, resources)) in rels.iter().enumerate() {
+                e.emit_map_elt_key(i, |e| rel.encode(e))?;
+                e.emit_map_elt_val(i, |e| {
+                    if !self.embedded_are_array(rel) {
+                        return resources[0].encode(e);
+                    }
+
+                    let mut items = lazy.remove(rel).map(Iterator::peekable);
+                    // An empty array is written differently, so it must be known up front
+                    let empty = resources.is_empty() && match items {
+                        Some(ref mut items) => items.peek().is_none(),
+                        None => true,
+                    };
+                    e.emit_seq(if empty { 0 } else { 1 }, |e| {
+                        let mut i = 0;
+                        for resource in resources {
+                            e.emit_seq_elt(i, |e| resource.encode(e))?;
+                            i += 1;
+                        }
+                        for mut resource in items.into_iter().flatten() {
+                            self.inherit(&mut resource);
+                            e.emit_seq_elt(i, |e| resource.encode(e))?;
+                            i += 1;
+                        }
+                        Ok(())
+                    })
+                })?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Writes the `_links` object, each rel as a single link or an array
+fn encode_rels<E, T, F>(e: &mut E, rels: Vec<(&str, &[T])>, as_array: F) -> Result<(), E::Error>
+    where E: Encoder,
+          T: Encodable,
+          F: Fn(&str) -> bool
+{
+    e.emit_map(rels.len(), |e| {
+        for (i, &(rel, values)) in rels.iter().enumerate() {
+            e.emit_map_elt_key(i, |e| rel.encode(e))?;
+            e.emit_map_elt_val(i, |e| {
+                if as_array(rel) {
+                    values.encode(e)
+                } else {
+                    values[0].encode(e)
+                }
+            })?;
+        }
+        Ok(())
+    })
+}
+
+impl<'a> From<&'a Resource> for Resource {
+    fn from(resource: &'a Resource) -> Resource {
+        resource.clone()
+    }
+}
+
+impl<'a> From<&'a mut Resource> for Resource {
+    fn from(resource: &'a mut Resource) -> Resource {
+        resource.clone()
+    }
+}
+
+impl TryFrom<Json> for Resource {
+    type Error = HalError;
+
+    fn try_from(json: Json) -> Result<Resource, HalError> {
+        Resource::parse(&json)
+    }
+}
@@ -0,0 +1,881 @@
+//! Serde support for the Hal types, enabled with the `serde` feature.
+//!
+//! Serialization writes the same wire format as `ToJson`, including the key
+//! order. Deserialization reads any self-describing format into a `HalState`
+//! tree and then validates it with the same rules as `Resource::parse`,
+//! except that `_templates` is read as HAL-FORMS templates.
+//!
+//! Any `Serialize` value can also be converted into Hal data, which is how
+//! `Resource::add_state_from` flattens a struct into resource state.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+                 SerializeStructVariant, SerializeTuple, SerializeTupleStruct,
+                 SerializeTupleVariant, Serializer};
+use serialize::json::ToJson;
+
+use error::HalError;
+use forms::Template;
+use link::Link;
+use resource::{Member, ParseOptions, Resource};
+use state::{HalList, HalState, ToHalState};
+use streaming::Lazy;
+
+impl Serialize for HalState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            HalState::I64(v) => serializer.serialize_i64(v),
+            HalState::F64(v) => serializer.serialize_f64(v),
+            HalState::U64(v) => serializer.serialize_u64(v),
+            HalState::String(ref v) => serializer.serialize_str(v),
+            HalState::Boolean(v) => serializer.serialize_bool(v),
+            HalState::Null => serializer.serialize_unit(),
+            HalState::List(ref v) => v.serialize(serializer),
+            HalState::Object(ref v) => v.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HalState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<HalState, D::Error> {
+        deserializer.deserialize_any(HalStateVisitor)
+    }
+}
+
+struct HalStateVisitor;
+
+impl<'de> Visitor<'de> for HalStateVisitor {
+    type Value = HalState;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a json compatible value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<HalState, E> {
+        Ok(HalState::Boolean(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<HalState, E> {
+        Ok(HalState::I64(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<HalState, E> {
+        Ok(HalState::U64(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<HalState, E> {
+        Ok(HalState::F64(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<HalState, E> {
+        Ok(HalState::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<HalState, E> {
+        Ok(HalState::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<HalState, E> {
+        Ok(HalState::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<HalState, E> {
+        Ok(HalState::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<HalState, D::Error> {
+        HalState::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<HalState, A::Error> {
+        let mut list = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            list.push(value);
+        }
+        Ok(HalState::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<HalState, A::Error> {
+        let mut object = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(HalState::Object(object))
+    }
+}
+
+impl Serialize for Link {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let optional = [&self.media_type, &self.deprecation, &self.name,
+                        &self.profile, &self.title, &self.hreflang];
+        let len = 1 + self.templated.iter().count() +
+                  optional.iter().filter(|value| value.is_some()).count();
+
+        // Members are written in the same (sorted) order as `ToJson`
+        let mut map = serializer.serialize_map(Some(len))?;
+        if let Some(ref deprecation) = self.deprecation {
+            map.serialize_entry("deprecation", deprecation)?;
+        }
+        map.serialize_entry("href", &self.href)?;
+        if let Some(ref hreflang) = self.hreflang {
+            map.serialize_entry("hreflang", hreflang)?;
+        }
+        if let Some(ref name) = self.name {
+            map.serialize_entry("name", name)?;
+        }
+        if let Some(ref profile) = self.profile {
+            map.serialize_entry("profile", profile)?;
+        }
+        if let Some(templated) = self.templated {
+            map.serialize_entry("templated", &templated)?;
+        }
+        if let Some(ref title) = self.title {
+            map.serialize_entry("title", title)?;
+        }
+        if let Some(ref media_type) = self.media_type {
+            map.serialize_entry("type", media_type)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Link {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Link, D::Error> {
+        let state = HalState::deserialize(deserializer)?;
+        Link::parse(&state.to_json()).map_err(de::Error::custom)
+    }
+}
+
+/// Templates are small, so they are serialized through `to_json` like
+/// `Encodable` does
+impl Serialize for Template {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().into_hal_state().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Template {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Template, D::Error> {
+        let state = HalState::deserialize(deserializer)?;
+        Template::parse(&state.to_json()).map_err(de::Error::custom)
+    }
+}
+
+/// A rel serialized as a single value, or as an array when it holds several
+/// values or must always be an array
+struct Rel<'a, T: 'a> {
+    values: &'a [T],
+    as_array: bool,
+}
+
+impl<'a, T: Serialize> Serialize for Rel<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.as_array || self.values.len() != 1 {
+            let mut seq = serializer.serialize_seq(Some(self.values.len()))?;
+            for value in self.values {
+                seq.serialize_element(value)?;
+            }
+            seq.end()
+        } else {
+            self.values[0].serialize(serializer)
+        }
+    }
+}
+
+struct Links<'a>(&'a Resource);
+
+impl<'a> Serialize for Links<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.links.len()))?;
+        for (rel, links) in self.0.links() {
+            map.serialize_entry(rel, &Rel { values: links, as_array: self.0.links_are_array(rel) })?;
+        }
+        map.end()
+    }
+}
+
+struct Embedded<'a, 'l: 'a>(&'a Resource, &'a RefCell<Lazy<'l>>);
+
+impl<'a, 'l> Serialize for Embedded<'a, 'l> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rels = self.0.embedded_members(&self.1.borrow());
+        let mut map = serializer.serialize_map(Some(rels.len()))?;
+        for (rel, resources) in rels {
+            let lazy = self.1.borrow_mut().remove(rel);
+            match lazy {
+                Some(lazy) => {
+                    map.serialize_entry(rel, &LazyRel { parent: self.0, resources, lazy: RefCell::new(Some(lazy)) })?
+                }
+                None => {
+                    map.serialize_entry(rel, &Rel { values: resources, as_array: self.0.embedded_are_array(rel) })?
+                }
+            }
+        }
+        map.end()
+    }
+}
+
+struct Templates<'a>(&'a Resource);
+
+impl<'a> Serialize for Templates<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for (key, template) in self.0.templates() {
+            map.serialize_entry(key, template)?;
+        }
+        map.end()
+    }
+}
+
+/// An embedded rel with lazy resources, which are consumed as it is serialized
+struct LazyRel<'a, 'l> {
+    parent: &'a Resource,
+    resources: &'a [Resource],
+    lazy: RefCell<Option<Box<dyn Iterator<Item = Resource> + 'l>>>,
+}
+
+impl<'a, 'l> Serialize for LazyRel<'a, 'l> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        for resource in self.resources {
+            seq.serialize_element(resource)?;
+        }
+        for mut resource in self.lazy.borrow_mut().take().into_iter().flatten() {
+            self.parent.inherit(&mut resource);
+            seq.serialize_element(&resource)?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for Resource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_streaming(self, Lazy::new(), serializer)
+    }
+}
+
+/// Serializes a resource, consuming the lazy resources of each rel as it is
+/// serialized
+pub(crate) fn serialize_streaming<S: Serializer>(resource: &Resource, lazy: Lazy, serializer: S)
+                                                 -> Result<S::Ok, S::Error> {
+    let members = resource.members_with(&lazy);
+    let lazy = RefCell::new(lazy);
+    let mut map = serializer.serialize_map(Some(members.len()))?;
+    for (key, member) in members {
+        match member {
+            Member::Links => map.serialize_entry(key, &Links(resource))?,
+            Member::Embedded => map.serialize_entry(key, &Embedded(resource, &lazy))?,
+            Member::Templates => map.serialize_entry(key, &Templates(resource))?,
+            Member::State(state) => map.serialize_entry(key, state)?,
+        }
+    }
+    map.end()
+}
+
+impl<'de> Deserialize<'de> for Resource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Resource, D::Error> {
+        let state = HalState::deserialize(deserializer)?;
+        // Serialization writes the templates, so they are read back as such
+        Resource::parse_json_with(&state.to_json(), ParseOptions::new().forms(true)).map_err(de::Error::custom)
+    }
+}
+
+impl ser::Error for HalError {
+    fn custom<T: fmt::Display>(msg: T) -> HalError {
+        HalError::Serialize(msg.to_string())
+    }
+}
+
+/// Converts a serializable value into Hal data
+pub fn to_hal_state<T: ?Sized + Serialize>(value: &T) -> Result<HalState, HalError> {
+    value.serialize(StateSerializer)
+}
+
+/// Converts a serializable struct or map into its fields in the order they
+/// are serialized, which for a struct is the order it declares them
+pub(crate) fn to_state_fields<T: ?Sized + Serialize>(value: &T) -> Result<Fields, HalError> {
+    value.serialize(FieldsSerializer)
+}
+
+/// A serializer whose output is a `HalState` tree instead of bytes
+struct StateSerializer;
+
+impl Serializer for StateSerializer {
+    type Ok = HalState;
+    type Error = HalError;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeVariant<SerializeList>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, v: bool) -> Result<HalState, HalError> {
+        Ok(HalState::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<HalState, HalError> {
+        Ok(HalState::I64(v as i64))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<HalState, HalError> {
+        Ok(HalState::I64(v as i64))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<HalState, HalError> {
+        Ok(HalState::I64(v as i64))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<HalState, HalError> {
+        Ok(HalState::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<HalState, HalError> {
+        Ok(HalState::U64(v as u64))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<HalState, HalError> {
+        Ok(HalState::U64(v as u64))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<HalState, HalError> {
+        Ok(HalState::U64(v as u64))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<HalState, HalError> {
+        Ok(HalState::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<HalState, HalError> {
+        Ok(HalState::F64(v as f64))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<HalState, HalError> {
+        Ok(HalState::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<HalState, HalError> {
+        Ok(HalState::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<HalState, HalError> {
+        Ok(HalState::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<HalState, HalError> {
+        Ok(HalState::List(v.iter().map(|b| HalState::U64(*b as u64)).collect()))
+    }
+
+    fn serialize_none(self) -> Result<HalState, HalError> {
+        Ok(HalState::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<HalState, HalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<HalState, HalError> {
+        Ok(HalState::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<HalState, HalError> {
+        Ok(HalState::Null)
+    }
+
+    fn serialize_unit_variant(self,
+                              _name: &'static str,
+                              _index: u32,
+                              variant: &'static str)
+                              -> Result<HalState, HalError> {
+        Ok(HalState::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self,
+                                                       _name: &'static str,
+                                                       value: &T)
+                                                       -> Result<HalState, HalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self,
+                                                        _name: &'static str,
+                                                        _index: u32,
+                                                        variant: &'static str,
+                                                        value: &T)
+                                                        -> Result<HalState, HalError> {
+        let mut object = BTreeMap::new();
+        object.insert(variant.to_string(), to_hal_state(value)?);
+        Ok(HalState::Object(object))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, HalError> {
+        Ok(SerializeList(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, HalError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self,
+                              _name: &'static str,
+                              len: usize)
+                              -> Result<SerializeList, HalError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self,
+                               _name: &'static str,
+                               _index: u32,
+                               variant: &'static str,
+                               len: usize)
+                               -> Result<SerializeVariant<SerializeList>, HalError> {
+        Ok(SerializeVariant(variant, SerializeList(Vec::with_capacity(len))))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, HalError> {
+        Ok(SerializeObject(Vec::new(), None))
+    }
+
+    fn serialize_struct(self,
+                        _name: &'static str,
+                        _len: usize)
+                        -> Result<SerializeObject, HalError> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(self,
+                                _name: &'static str,
+                                _index: u32,
+                                variant: &'static str,
+                                _len: usize)
+                                -> Result<SerializeVariant<SerializeObject>, HalError> {
+        Ok(SerializeVariant(variant, SerializeObject(Vec::new(), None)))
+    }
+}
+
+struct SerializeList(HalList);
+
+impl SerializeSeq for SerializeList {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        self.0.push(to_hal_state(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        Ok(HalState::List(self.0))
+    }
+}
+
+impl SerializeTuple for SerializeList {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SerializeList {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// The fields of a struct or map in the order they are serialized
+pub(crate) type Fields = Vec<(String, HalState)>;
+
+/// Collects the members of a map or struct in order; the second field holds
+/// a map key until its value arrives
+struct SerializeObject(Fields, Option<String>);
+
+impl SerializeObject {
+    /// Adds a member, replacing the value of a key seen before in place
+    fn insert(&mut self, key: String, value: HalState) {
+        match self.0.iter_mut().find(|field| field.0 == key) {
+            Some(field) => field.1 = value,
+            None => self.0.push((key, value)),
+        }
+    }
+}
+
+impl SerializeMap for SerializeObject {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), HalError> {
+        self.1 = Some(match key.serialize(StateSerializer)? {
+            HalState::String(key) => key,
+            HalState::I64(key) => key.to_string(),
+            HalState::U64(key) => key.to_string(),
+            _ => return Err(HalError::Serialize("map keys must be strings".to_string())),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        let key = self.1.take().expect("serialize_value called before serialize_key");
+        let value = to_hal_state(value)?;
+        self.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        Ok(HalState::Object(self.0.into_iter().collect()))
+    }
+}
+
+impl SerializeStruct for SerializeObject {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self,
+                                              key: &'static str,
+                                              value: &T)
+                                              -> Result<(), HalError> {
+        let value = to_hal_state(value)?;
+        self.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        SerializeMap::end(self)
+    }
+}
+
+/// Wraps the output of an enum variant in an object keyed by the variant name
+struct SerializeVariant<S>(&'static str, S);
+
+impl SerializeTupleVariant for SerializeVariant<SerializeList> {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(&mut self.1, value)
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        let mut object = BTreeMap::new();
+        object.insert(self.0.to_string(), SerializeSeq::end(self.1)?);
+        Ok(HalState::Object(object))
+    }
+}
+
+impl SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = HalState;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self,
+                                              key: &'static str,
+                                              value: &T)
+                                              -> Result<(), HalError> {
+        SerializeStruct::serialize_field(&mut self.1, key, value)
+    }
+
+    fn end(self) -> Result<HalState, HalError> {
+        let mut object = BTreeMap::new();
+        object.insert(self.0.to_string(), SerializeStruct::end(self.1)?);
+        Ok(HalState::Object(object))
+    }
+}
+
+/// A serializer for the top level of `Resource::add_state_from`, which keeps
+/// the fields of a struct or map in order and rejects anything else
+struct FieldsSerializer;
+
+impl FieldsSerializer {
+    /// Turns a value that is not a struct or map into its fields, if it
+    /// still serializes to an object
+    fn fields(state: Result<HalState, HalError>) -> Result<Fields, HalError> {
+        match state? {
+            HalState::Object(fields) => Ok(fields.into_iter().collect()),
+            other => {
+                Err(HalError::WrongType {
+                    path: "".to_string(),
+                    expected: "object",
+                    found: ::state::state_type(&other),
+                })
+            }
+        }
+    }
+}
+
+impl Serializer for FieldsSerializer {
+    type Ok = Fields;
+    type Error = HalError;
+
+    type SerializeSeq = AsFields<SerializeList>;
+    type SerializeTuple = AsFields<SerializeList>;
+    type SerializeTupleStruct = AsFields<SerializeList>;
+    type SerializeTupleVariant = AsFields<SerializeVariant<SerializeList>>;
+    type SerializeMap = SerializeFields;
+    type SerializeStruct = SerializeFields;
+    type SerializeStructVariant = AsFields<SerializeVariant<SerializeObject>>;
+
+    fn serialize_bool(self, v: bool) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_i8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_i16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_i32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_i64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_u8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_u16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_u32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_u64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_f32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_f64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_str(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_bytes(v))
+    }
+
+    fn serialize_none(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_none())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Fields, HalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_unit())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_unit_struct(name))
+    }
+
+    fn serialize_unit_variant(self,
+                              name: &'static str,
+                              index: u32,
+                              variant: &'static str)
+                              -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_unit_variant(name, index, variant))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self,
+                                                       _name: &'static str,
+                                                       value: &T)
+                                                       -> Result<Fields, HalError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self,
+                                                        name: &'static str,
+                                                        index: u32,
+                                                        variant: &'static str,
+                                                        value: &T)
+                                                        -> Result<Fields, HalError> {
+        FieldsSerializer::fields(StateSerializer.serialize_newtype_variant(name, index, variant, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<AsFields<SerializeList>, HalError> {
+        StateSerializer.serialize_seq(len).map(AsFields)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<AsFields<SerializeList>, HalError> {
+        StateSerializer.serialize_tuple(len).map(AsFields)
+    }
+
+    fn serialize_tuple_struct(self,
+                              name: &'static str,
+                              len: usize)
+                              -> Result<AsFields<SerializeList>, HalError> {
+        StateSerializer.serialize_tuple_struct(name, len).map(AsFields)
+    }
+
+    fn serialize_tuple_variant(self,
+                               name: &'static str,
+                               index: u32,
+                               variant: &'static str,
+                               len: usize)
+                               -> Result<AsFields<SerializeVariant<SerializeList>>, HalError> {
+        StateSerializer.serialize_tuple_variant(name, index, variant, len).map(AsFields)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeFields, HalError> {
+        Ok(SerializeFields(SerializeObject(Vec::new(), None)))
+    }
+
+    fn serialize_struct(self,
+                        _name: &'static str,
+                        _len: usize)
+                        -> Result<SerializeFields, HalError> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(self,
+                                name: &'static str,
+                                index: u32,
+                                variant: &'static str,
+                                len: usize)
+                                -> Result<AsFields<SerializeVariant<SerializeObject>>, HalError> {
+        StateSerializer.serialize_struct_variant(name, index, variant, len).map(AsFields)
+    }
+}
+
+/// Collects the top level fields for `FieldsSerializer`
+struct SerializeFields(SerializeObject);
+
+impl SerializeMap for SerializeFields {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), HalError> {
+        self.0.serialize_key(key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        self.0.serialize_value(value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        Ok((self.0).0)
+    }
+}
+
+impl SerializeStruct for SerializeFields {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self,
+                                              key: &'static str,
+                                              value: &T)
+                                              -> Result<(), HalError> {
+        SerializeStruct::serialize_field(&mut self.0, key, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        Ok((self.0).0)
+    }
+}
+
+/// Serializes a value that is not a struct or map as Hal data, and then
+/// takes the fields of the object it becomes, if it is one
+struct AsFields<S>(S);
+
+impl SerializeSeq for AsFields<SerializeList> {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(SerializeSeq::end(self.0))
+    }
+}
+
+impl SerializeTuple for AsFields<SerializeList> {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(SerializeSeq::end(self.0))
+    }
+}
+
+impl SerializeTupleStruct for AsFields<SerializeList> {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeSeq::serialize_element(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(SerializeSeq::end(self.0))
+    }
+}
+
+impl SerializeTupleVariant for AsFields<SerializeVariant<SerializeList>> {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), HalError> {
+        SerializeTupleVariant::serialize_field(&mut self.0, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(SerializeTupleVariant::end(self.0))
+    }
+}
+
+impl SerializeStructVariant for AsFields<SerializeVariant<SerializeObject>> {
+    type Ok = Fields;
+    type Error = HalError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self,
+                                              key: &'static str,
+                                              value: &T)
+                                              -> Result<(), HalError> {
+        SerializeStructVariant::serialize_field(&mut self.0, key, value)
+    }
+
+    fn end(self) -> Result<Fields, HalError> {
+        FieldsSerializer::fields(SerializeStructVariant::end(self.0))
+    }
+}
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use serialize::json::{ToJson, Json};
+use serialize::{Encodable, Encoder};
+
+use error::{HalError, pointer};
 
 /// Represents a Hal data value
 #[derive(Clone, PartialEq, Debug)]
@@ -25,6 +29,14 @@ pub type HalObject = BTreeMap<String, HalState>;
 pub trait ToHalState {
     /// Converts the value of `self` to an instance of HalState
     fn to_hal_state(&self) -> HalState;
+
+    /// Converts `self` into an instance of HalState, moving strings and
+    /// collections rather than copying them where it can
+    fn into_hal_state(self) -> HalState
+        where Self: Sized
+    {
+        self.to_hal_state()
+    }
 }
 
 macro_rules! to_hal_state_impl_i64 {
@@ -69,6 +81,10 @@ impl ToHalState for String {
     fn to_hal_state(&self) -> HalState {
         HalState::String((*self).clone())
     }
+
+    fn into_hal_state(self) -> HalState {
+        HalState::String(self)
+    }
 }
 
 impl ToHalState for &'static str {
@@ -87,6 +103,10 @@ impl<T: ToHalState> ToHalState for Vec<T> {
     fn to_hal_state(&self) -> HalState {
         HalState::List(self.iter().map(|elt| elt.to_hal_state()).collect())
     }
+
+    fn into_hal_state(self) -> HalState {
+        HalState::List(self.into_iter().map(|elt| elt.into_hal_state()).collect())
+    }
 }
 
 impl<T: ToHalState> ToHalState for BTreeMap<String, T> {
@@ -97,6 +117,10 @@ impl<T: ToHalState> ToHalState for BTreeMap<String, T> {
         }
         HalState::Object(t)
     }
+
+    fn into_hal_state(self) -> HalState {
+        HalState::Object(self.into_iter().map(|(key, value)| (key, value.into_hal_state())).collect())
+    }
 }
 
 impl<T: ToHalState> ToHalState for HashMap<String, T> {
@@ -107,6 +131,10 @@ impl<T: ToHalState> ToHalState for HashMap<String, T> {
         }
         HalState::Object(t)
     }
+
+    fn into_hal_state(self) -> HalState {
+        HalState::Object(self.into_iter().map(|(key, value)| (key, value.into_hal_state())).collect())
+    }
 }
 
 impl<T: ToHalState> ToHalState for Option<T> {
@@ -116,6 +144,23 @@ impl<T: ToHalState> ToHalState for Option<T> {
             Some(ref value) => value.to_hal_state(),
         }
     }
+
+    fn into_hal_state(self) -> HalState {
+        match self {
+            None => HalState::Null,
+            Some(value) => value.into_hal_state(),
+        }
+    }
+}
+
+impl ToHalState for HalState {
+    fn to_hal_state(&self) -> HalState {
+        self.clone()
+    }
+
+    fn into_hal_state(self) -> HalState {
+        self
+    }
 }
 
 impl ToHalState for Json {
@@ -131,6 +176,15 @@ impl ToHalState for Json {
             Json::Null => ().to_hal_state(),
         }
     }
+
+    fn into_hal_state(self) -> HalState {
+        match self {
+            Json::String(v) => v.into_hal_state(),
+            Json::Array(v) => v.into_hal_state(),
+            Json::Object(v) => v.into_hal_state(),
+            other => other.to_hal_state(),
+        }
+    }
 }
 
 impl ToJson for HalState {
@@ -147,3 +201,164 @@ impl ToJson for HalState {
         }
     }
 }
+
+impl Encodable for HalState {
+    fn encode<E: Encoder>(&self, e: &mut E) -> Result<(), E::Error> {
+        match *self {
+            HalState::I64(v) => v.encode(e),
+            HalState::F64(v) => v.encode(e),
+            HalState::U64(v) => v.encode(e),
+            HalState::String(ref v) => v.encode(e),
+            HalState::Boolean(v) => v.encode(e),
+            HalState::Null => e.emit_nil(),
+            HalState::List(ref v) => v.encode(e),
+            HalState::Object(ref v) => v.encode(e),
+        }
+    }
+}
+
+/// A trait for converting Hal data back into values
+pub trait FromHalState: Sized {
+    /// Converts an instance of HalState into a value of `Self`
+    ///
+    /// Error paths are relative to `state`.
+    fn from_hal_state(state: &HalState) -> Result<Self, HalError>;
+
+    /// The value to use when the state is absent, if there is one
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Names the json type of a HalState for use in error messages
+pub(crate) fn state_type(state: &HalState) -> &'static str {
+    match *state {
+        HalState::I64(_) | HalState::F64(_) | HalState::U64(_) => "number",
+        HalState::String(_) => "string",
+        HalState::Boolean(_) => "boolean",
+        HalState::Null => "null",
+        HalState::List(_) => "array",
+        HalState::Object(_) => "object",
+    }
+}
+
+fn wrong_type(expected: &'static str, state: &HalState) -> HalError {
+    HalError::WrongType {
+        path: "".to_string(),
+        expected,
+        found: state_type(state),
+    }
+}
+
+macro_rules! from_hal_state_impl_integer {
+    ($($t:ident), +) => (
+        $(impl FromHalState for $t {
+            fn from_hal_state(state: &HalState) -> Result<$t, HalError> {
+                let value = match *state {
+                    HalState::I64(v) => $t::try_from(v).ok(),
+                    HalState::U64(v) => $t::try_from(v).ok(),
+                    _ => None,
+                };
+                value.ok_or_else(|| wrong_type(stringify!($t), state))
+            }
+        })+
+    )
+}
+
+from_hal_state_impl_integer! { isize, i8, i16, i32, i64, usize, u8, u16, u32, u64 }
+
+impl FromHalState for f64 {
+    fn from_hal_state(state: &HalState) -> Result<f64, HalError> {
+        match *state {
+            HalState::F64(v) => Ok(v),
+            HalState::I64(v) => Ok(v as f64),
+            HalState::U64(v) => Ok(v as f64),
+            _ => Err(wrong_type("number", state)),
+        }
+    }
+}
+
+impl FromHalState for () {
+    fn from_hal_state(state: &HalState) -> Result<(), HalError> {
+        match *state {
+            HalState::Null => Ok(()),
+            _ => Err(wrong_type("null", state)),
+        }
+    }
+}
+
+impl FromHalState for bool {
+    fn from_hal_state(state: &HalState) -> Result<bool, HalError> {
+        match *state {
+            HalState::Boolean(v) => Ok(v),
+            _ => Err(wrong_type("boolean", state)),
+        }
+    }
+}
+
+impl FromHalState for String {
+    fn from_hal_state(state: &HalState) -> Result<String, HalError> {
+        match *state {
+            HalState::String(ref v) => Ok(v.clone()),
+            _ => Err(wrong_type("string", state)),
+        }
+    }
+}
+
+impl<T: FromHalState> FromHalState for Vec<T> {
+    fn from_hal_state(state: &HalState) -> Result<Vec<T>, HalError> {
+        match *state {
+            HalState::List(ref list) => {
+                list.iter()
+                    .enumerate()
+                    .map(|(i, elt)| {
+                        T::from_hal_state(elt).map_err(|e| e.nested_in(&pointer("", &i.to_string())))
+                    })
+                    .collect()
+            }
+            _ => Err(wrong_type("array", state)),
+        }
+    }
+}
+
+impl<T: FromHalState> FromHalState for BTreeMap<String, T> {
+    fn from_hal_state(state: &HalState) -> Result<BTreeMap<String, T>, HalError> {
+        match *state {
+            HalState::Object(ref object) => {
+                object.iter()
+                    .map(|(key, value)| {
+                        T::from_hal_state(value)
+                            .map(|value| (key.clone(), value))
+                            .map_err(|e| e.nested_in(&pointer("", key)))
+                    })
+                    .collect()
+            }
+            _ => Err(wrong_type("object", state)),
+        }
+    }
+}
+
+impl<T: FromHalState> FromHalState for HashMap<String, T> {
+    fn from_hal_state(state: &HalState) -> Result<HashMap<String, T>, HalError> {
+        BTreeMap::from_hal_state(state).map(|map: BTreeMap<String, T>| map.into_iter().collect())
+    }
+}
+
+impl<T: FromHalState> FromHalState for Option<T> {
+    fn from_hal_state(state: &HalState) -> Result<Option<T>, HalError> {
+        match *state {
+            HalState::Null => Ok(None),
+            _ => T::from_hal_state(state).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Option<T>> {
+        Some(None)
+    }
+}
+
+impl FromHalState for HalState {
+    fn from_hal_state(state: &HalState) -> Result<HalState, HalError> {
+        Ok(state.clone())
+    }
+}
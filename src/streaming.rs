@@ -0,0 +1,130 @@
+//! Resources that embed resources from iterators.
+//!
+//! A large collection, such as rows from a database, can be embedded without
+//! building every resource up front. `Resource::into_streaming` turns a
+//! resource into a `StreamingResource`, which takes iterators of `ToHal`
+//! items and consumes them when it is written. Writing consumes the
+//! `StreamingResource` too, so each iterator is only ever consumed once.
+//!
+//! ```
+//! extern crate hal;
+//!
+//! use hal::ToHal;
+//! use hal::resource::Resource;
+//! use hal::writer;
+//!
+//! struct Order {
+//!     id: u32,
+//! }
+//!
+//! impl ToHal for Order {
+//!     fn to_hal(self) -> Resource {
+//!         Resource::with_self(format!("/orders/{}", self.id))
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut orders = Resource::with_self("/orders").into_streaming();
+//!     orders.add_lazy_resources("orders", (1..4).map(|id| Order { id }));
+//!
+//!     let mut out = Vec::new();
+//!     writer::stream(&mut out, orders).unwrap();
+//!     assert!(String::from_utf8(out).unwrap().contains("/orders/3"));
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use serialize::json::{Json, ToJson};
+use serialize::Encoder;
+
+use resource::Resource;
+use ToHal;
+
+/// The lazy resources of each embedded rel
+pub(crate) type Lazy<'a> = BTreeMap<String, Box<dyn Iterator<Item = Resource> + 'a>>;
+
+/// A resource with embedded resources that are only produced as it is written
+///
+/// The lazy resources of a rel come after any embedded in the resource and
+/// are always written as an array. The iterators may borrow, such as rows
+/// read from a database connection, for as long as `'a`.
+pub struct StreamingResource<'a> {
+    resource: Resource,
+    lazy: Lazy<'a>,
+}
+
+impl<'a> StreamingResource<'a> {
+    pub(crate) fn new(resource: Resource) -> StreamingResource<'a> {
+        StreamingResource { resource, lazy: BTreeMap::new() }
+    }
+
+    /// Embed the resources of a to-many rel from an iterator, which is
+    /// consumed when this resource is written
+    ///
+    /// Adding the same rel again replaces its iterator.
+    pub fn add_lazy_resources<S, I>(&mut self, rel: S, items: I) -> &mut StreamingResource<'a>
+        where S: Into<String>,
+              I: IntoIterator,
+              I::IntoIter: 'a,
+              I::Item: ToHal + 'a
+    {
+        let rel = self.resource.embed_lazily(rel.into());
+        self.lazy.insert(rel, Box::new(items.into_iter().map(ToHal::to_hal)));
+        self
+    }
+
+    /// The resource without its lazy resources, which the accessors,
+    /// `validate_curies` and `hal::lint` do not see
+    pub fn resource(&self) -> &Resource {
+        &self.resource
+    }
+
+    /// Builds the document, with the lazy resources after the resources
+    /// embedded with the same rel
+    pub fn into_json(self) -> Json {
+        let StreamingResource { resource, lazy } = self;
+        let mut hal = match resource.to_json() {
+            Json::Object(hal) => hal,
+            _ => unreachable!("a resource is always a json object"),
+        };
+
+        if !lazy.is_empty() {
+            let embedded = hal.entry("_embedded".to_string()).or_insert_with(|| Json::Object(BTreeMap::new()));
+            if let Json::Object(ref mut rels) = *embedded {
+                for (rel, items) in lazy {
+                    let values = rels.entry(rel).or_insert_with(|| Json::Array(Vec::new()));
+                    if let Json::Array(ref mut values) = *values {
+                        values.extend(items.map(|mut item| {
+                            resource.inherit(&mut item);
+                            item.to_json()
+                        }));
+                    }
+                }
+            }
+        }
+
+        Json::Object(hal)
+    }
+
+    /// Writes the document, consuming the lazy resources as they are written
+    pub(crate) fn encode<E: Encoder>(mut self, e: &mut E) -> Result<(), E::Error> {
+        self.resource.encode_with(e, &mut self.lazy)
+    }
+
+    /// Serializes the document, consuming the lazy resources as they are
+    /// serialized
+    #[cfg(feature = "serde")]
+    pub fn serialize<S: ::serde::Serializer>(self, serializer: S) -> Result<S::Ok, S::Error> {
+        ::serde_impl::serialize_streaming(&self.resource, self.lazy, serializer)
+    }
+}
+
+impl<'a> fmt::Debug for StreamingResource<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamingResource")
+            .field("resource", &self.resource)
+            .field("lazy", &self.lazy.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
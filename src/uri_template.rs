@@ -0,0 +1,408 @@
+//! URI Template expansion as defined by RFC 6570, up to and including level 4.
+//!
+//! Variables are supplied as Hal data. Strings, numbers and booleans are
+//! simple values, lists and objects are the RFC's lists and associative
+//! arrays, and `Null` (like an empty list or object) is undefined.
+
+use std::fmt::Write;
+
+use error::HalError;
+use state::{HalObject, HalState};
+
+/// A parsed URI Template
+#[derive(Clone, PartialEq, Debug)]
+pub struct UriTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Part {
+    Literal(String),
+    Expression(Operator, Vec<VarSpec>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct VarSpec {
+    name: String,
+    modifier: Modifier,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    Path,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Operator> {
+        match c {
+            '+' => Some(Operator::Reserved),
+            '#' => Some(Operator::Fragment),
+            '.' => Some(Operator::Label),
+            '/' => Some(Operator::Path),
+            ';' => Some(Operator::PathParameter),
+            '?' => Some(Operator::Query),
+            '&' => Some(Operator::QueryContinuation),
+            _ => None,
+        }
+    }
+
+    /// The string written before the first defined variable
+    fn first(self) -> &'static str {
+        match self {
+            Operator::Simple | Operator::Reserved => "",
+            Operator::Fragment => "#",
+            Operator::Label => ".",
+            Operator::Path => "/",
+            Operator::PathParameter => ";",
+            Operator::Query => "?",
+            Operator::QueryContinuation => "&",
+        }
+    }
+
+    /// The string written between defined variables
+    fn separator(self) -> &'static str {
+        match self {
+            Operator::Simple | Operator::Reserved | Operator::Fragment => ",",
+            Operator::Label => ".",
+            Operator::Path => "/",
+            Operator::PathParameter => ";",
+            Operator::Query | Operator::QueryContinuation => "&",
+        }
+    }
+
+    /// Whether variables are written as `name=value` pairs
+    fn named(self) -> bool {
+        matches!(self, Operator::PathParameter | Operator::Query | Operator::QueryContinuation)
+    }
+
+    /// The string written after the name of a variable with an empty value
+    fn if_empty(self) -> &'static str {
+        match self {
+            Operator::Query | Operator::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+
+    /// Whether reserved characters are written without being pct-encoded
+    fn allow_reserved(self) -> bool {
+        matches!(self, Operator::Reserved | Operator::Fragment)
+    }
+}
+
+impl UriTemplate {
+    /// Parse a URI Template, failing if an expression is malformed
+    pub fn parse(template: &str) -> Result<UriTemplate, HalError> {
+        let error = |position: usize, reason: &'static str| {
+            HalError::InvalidTemplate {
+                template: template.to_string(),
+                position,
+                reason,
+            }
+        };
+
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+
+        while !rest.is_empty() {
+            let position = template.len() - rest.len();
+            if rest.starts_with('}') {
+                return Err(error(position, "unmatched `}`"));
+            }
+            if !rest.starts_with('{') {
+                let c = rest.chars().next().unwrap();
+                literal.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+
+            let end = match rest.find('}') {
+                Some(end) => end,
+                None => return Err(error(position, "unclosed `{`")),
+            };
+            let expression = &rest[1..end];
+            if let Some(offset) = expression.find('{') {
+                return Err(error(position + 1 + offset, "nested `{`"));
+            }
+            if !literal.is_empty() {
+                parts.push(Part::Literal(encode_literal(&literal)));
+                literal.clear();
+            }
+            parts.push(parse_expression(expression, position + 1, &error)?);
+            rest = &rest[end + 1..];
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(encode_literal(&literal)));
+        }
+
+        Ok(UriTemplate { parts })
+    }
+
+    /// Whether the template contains any expressions
+    pub fn has_expressions(&self) -> bool {
+        self.parts.iter().any(|part| match *part {
+            Part::Expression(..) => true,
+            Part::Literal(_) => false,
+        })
+    }
+
+    /// The names of the variables used by the template, in order of use
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for part in self.parts.iter() {
+            if let Part::Expression(_, ref varspecs) = *part {
+                for varspec in varspecs.iter() {
+                    if !names.contains(&&varspec.name[..]) {
+                        names.push(&varspec.name[..]);
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    /// Expand the template into a URI reference
+    pub fn expand(&self, vars: &HalObject) -> String {
+        let mut uri = String::new();
+        for part in self.parts.iter() {
+            match *part {
+                Part::Literal(ref literal) => uri.push_str(literal),
+                Part::Expression(operator, ref varspecs) => {
+                    expand_expression(&mut uri, operator, varspecs, vars)
+                }
+            }
+        }
+        uri
+    }
+}
+
+fn parse_expression<F>(expression: &str, position: usize, error: &F) -> Result<Part, HalError>
+    where F: Fn(usize, &'static str) -> HalError
+{
+    let mut chars = expression.chars();
+    let (operator, list, offset) = match chars.next() {
+        None => return Err(error(position, "empty expression")),
+        Some(c) if "=,!@|".contains(c) => {
+            return Err(error(position, "operator is reserved for future extensions"))
+        }
+        Some(c) => {
+            match Operator::from_char(c) {
+                Some(operator) => (operator, chars.as_str(), position + 1),
+                None => (Operator::Simple, expression, position),
+            }
+        }
+    };
+
+    let mut varspecs = Vec::new();
+    let mut offset = offset;
+    for varspec in list.split(',') {
+        varspecs.push(parse_varspec(varspec, offset, error)?);
+        offset += varspec.len() + 1;
+    }
+
+    Ok(Part::Expression(operator, varspecs))
+}
+
+fn parse_varspec<F>(varspec: &str, position: usize, error: &F) -> Result<VarSpec, HalError>
+    where F: Fn(usize, &'static str) -> HalError
+{
+    let (name, modifier) = if let Some(name) = varspec.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some(colon) = varspec.find(':') {
+        let length = &varspec[colon + 1..];
+        let valid = !length.is_empty() && length.len() <= 4 && !length.starts_with('0') &&
+                    length.bytes().all(|b| b.is_ascii_digit());
+        if !valid {
+            return Err(error(position + colon + 1, "prefix length must be between 1 and 9999"));
+        }
+        (&varspec[..colon], Modifier::Prefix(length.parse().unwrap()))
+    } else {
+        (varspec, Modifier::None)
+    };
+
+    if name.is_empty() {
+        return Err(error(position, "missing variable name"));
+    }
+
+    let bytes = name.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let valid = if b == b'%' {
+            let triplet = i + 2 < bytes.len() && bytes[i + 1].is_ascii_hexdigit() &&
+                          bytes[i + 2].is_ascii_hexdigit();
+            i += 2;
+            triplet
+        } else if b == b'.' {
+            i > 0 && i + 1 < bytes.len() && bytes[i - 1] != b'.'
+        } else {
+            b.is_ascii_alphanumeric() || b == b'_'
+        };
+        if !valid {
+            return Err(error(position, "invalid character in variable name"));
+        }
+        i += 1;
+    }
+
+    Ok(VarSpec {
+        name: name.to_string(),
+        modifier,
+    })
+}
+
+fn expand_expression(uri: &mut String, operator: Operator, varspecs: &[VarSpec], vars: &HalObject) {
+    let allow_reserved = operator.allow_reserved();
+    let mut first = true;
+
+    for varspec in varspecs.iter() {
+        let value = match vars.get(&varspec.name) {
+            Some(value) if is_defined(value) => value,
+            _ => continue,
+        };
+
+        uri.push_str(if first { operator.first() } else { operator.separator() });
+        first = false;
+
+        match *value {
+            HalState::List(ref list) => {
+                let items = list.iter().filter_map(scalar);
+                if varspec.modifier == Modifier::Explode {
+                    let mut sep = "";
+                    for item in items {
+                        uri.push_str(sep);
+                        if operator.named() {
+                            push_pair(uri, operator, &varspec.name, &item, allow_reserved);
+                        } else {
+                            uri.push_str(&encode(&item, allow_reserved));
+                        }
+                        sep = operator.separator();
+                    }
+                } else {
+                    if operator.named() {
+                        uri.push_str(&varspec.name);
+                        uri.push('=');
+                    }
+                    let items: Vec<String> = items.map(|item| encode(&item, allow_reserved)).collect();
+                    uri.push_str(&items.join(","));
+                }
+            }
+            HalState::Object(ref object) => {
+                let pairs = object.iter().filter_map(|(k, v)| scalar(v).map(|v| (k, v)));
+                if varspec.modifier == Modifier::Explode {
+                    let mut sep = "";
+                    for (key, value) in pairs {
+                        uri.push_str(sep);
+                        if operator.named() {
+                            push_pair(uri, operator, key, &value, allow_reserved);
+                        } else {
+                            uri.push_str(&encode(key, allow_reserved));
+                            uri.push('=');
+                            uri.push_str(&encode(&value, allow_reserved));
+                        }
+                        sep = operator.separator();
+                    }
+                } else {
+                    if operator.named() {
+                        uri.push_str(&varspec.name);
+                        uri.push('=');
+                    }
+                    let pairs: Vec<String> = pairs.map(|(key, value)| {
+                            format!("{},{}", encode(key, allow_reserved), encode(&value, allow_reserved))
+                        })
+                        .collect();
+                    uri.push_str(&pairs.join(","));
+                }
+            }
+            ref value => {
+                let mut value = scalar(value).unwrap_or_default();
+                if let Modifier::Prefix(length) = varspec.modifier {
+                    value = value.chars().take(length).collect();
+                }
+                push_pair(uri, operator, &varspec.name, &value, allow_reserved);
+            }
+        }
+    }
+}
+
+/// Writes a value, preceded by its name when the operator names variables
+fn push_pair(uri: &mut String, operator: Operator, name: &str, value: &str, allow_reserved: bool) {
+    if operator.named() {
+        uri.push_str(&encode(name, allow_reserved));
+        if value.is_empty() {
+            uri.push_str(operator.if_empty());
+            return;
+        }
+        uri.push('=');
+    }
+    uri.push_str(&encode(value, allow_reserved));
+}
+
+/// Empty lists and objects, like null, are undefined
+fn is_defined(value: &HalState) -> bool {
+    match *value {
+        HalState::Null => false,
+        HalState::List(ref list) => list.iter().any(|item| scalar(item).is_some()),
+        HalState::Object(ref object) => object.values().any(|item| scalar(item).is_some()),
+        _ => true,
+    }
+}
+
+/// The string form of a simple value
+fn scalar(value: &HalState) -> Option<String> {
+    match *value {
+        HalState::String(ref v) => Some(v.clone()),
+        HalState::I64(v) => Some(v.to_string()),
+        HalState::U64(v) => Some(v.to_string()),
+        HalState::F64(v) => Some(v.to_string()),
+        HalState::Boolean(v) => Some(v.to_string()),
+        HalState::Null | HalState::List(_) | HalState::Object(_) => None,
+    }
+}
+
+fn is_unreserved(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'-' || b == b'.' || b == b'_' || b == b'~'
+}
+
+fn is_reserved(b: u8) -> bool {
+    b":/?#[]@!$&'()*+,;=".contains(&b)
+}
+
+/// Pct-encodes everything but unreserved characters and, if allowed, reserved
+/// characters and existing pct-encoded triplets
+fn encode(value: &str, allow_reserved: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut encoded = String::with_capacity(value.len());
+    for (i, &b) in bytes.iter().enumerate() {
+        let keep = is_unreserved(b) ||
+                   allow_reserved && (is_reserved(b) || b == b'%' && is_triplet(&bytes[i..]));
+        if keep {
+            encoded.push(b as char);
+        } else {
+            write!(encoded, "%{:02X}", b).unwrap();
+        }
+    }
+    encoded
+}
+
+fn encode_literal(literal: &str) -> String {
+    encode(literal, true)
+}
+
+fn is_triplet(bytes: &[u8]) -> bool {
+    bytes.len() >= 3 && bytes[1].is_ascii_hexdigit() && bytes[2].is_ascii_hexdigit()
+}
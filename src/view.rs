@@ -0,0 +1,739 @@
+//! Borrowed views of Hal documents.
+//!
+//! `ResourceRef` reads a Hal document from json text without building a
+//! `Resource`. Reading checks that the text is well formed json and reads
+//! the links and embedded resources; state and templates are only found in
+//! the text, and each is read the first time it is asked for. Strings borrow
+//! from the text unless they contain escapes.
+//!
+//! Links and embedded resources of the wrong shape are reported where they
+//! are when the text is read, as `Resource::parse` does.
+//!
+//! ```
+//! use hal::view::ResourceRef;
+//!
+//! let text = r#"{"_links":{"self":{"href":"/orders"},"next":{"href":"/orders?page=2"}},"total":14}"#;
+//! let hal = ResourceRef::parse(text).unwrap();
+//!
+//! let next = hal.get_link("next").unwrap();
+//! assert_eq!(next.get_href(), "/orders?page=2");
+//! assert_eq!(hal.get_state_as::<i64>("total"), Ok(14));
+//! ```
+
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use serialize::json::{ErrorCode, Json, JsonEvent, Parser, ParserError};
+
+use curie;
+use error::{HalError, pointer};
+use forms::Template;
+use link::Link;
+use resource::{self, Resource, RESERVED_KEYS};
+use state::{FromHalState, HalState, ToHalState};
+
+/// The members of a json object, each key decoded, with the text of its value
+type Members<'a> = Vec<(Cow<'a, str>, &'a str)>;
+
+/// A Hal resource read from json text
+#[derive(Clone)]
+pub struct ResourceRef<'a> {
+    text: &'a str,
+    members: Members<'a>,
+    /// The state read from each member, in the order of `members`
+    states: Vec<OnceCell<HalState>>,
+    links: Vec<(Cow<'a, str>, Vec<LinkRef<'a>>)>,
+    embedded: Vec<(Cow<'a, str>, Vec<ResourceRef<'a>>)>,
+}
+
+/// A Hal link read from json text
+#[derive(Clone, PartialEq, Debug)]
+pub struct LinkRef<'a> {
+    href: Cow<'a, str>,
+    templated: Option<bool>,
+    media_type: Option<Cow<'a, str>>,
+    deprecation: Option<Cow<'a, str>>,
+    name: Option<Cow<'a, str>>,
+    profile: Option<Cow<'a, str>>,
+    title: Option<Cow<'a, str>>,
+    hreflang: Option<Cow<'a, str>>,
+}
+
+impl<'a> ResourceRef<'a> {
+    /// Read json text as a Hal resource
+    ///
+    /// Fails if the text is not well formed json, is not a json object or
+    /// has links or embedded resources that are malformed.
+    pub fn parse(text: &'a str) -> Result<ResourceRef<'a>, HalError> {
+        match check(text) {
+            Ok(value) => ResourceRef::at(value),
+            Err(at) => Err(syntax_error(text, at)),
+        }
+    }
+
+    /// A view of the resource in checked json text, reading its links and
+    /// embedded resources
+    ///
+    /// Paths in errors are relative to the resource.
+    fn at(text: &'a str) -> Result<ResourceRef<'a>, HalError> {
+        let members = object_at(text, "")?;
+
+        let mut links = Vec::new();
+        if let Some(value) = find(&members, "_links") {
+            for (rel, value) in object_at(value, "/_links")? {
+                let rel_links = read_rel(value, LinkRef::at).map_err(|e| e.nested_in(&pointer("/_links", &rel)))?;
+                links.push((rel, rel_links));
+            }
+        }
+
+        let mut embedded = Vec::new();
+        if let Some(value) = find(&members, "_embedded") {
+            for (rel, value) in object_at(value, "/_embedded")? {
+                let resources = read_rel(value, ResourceRef::at).map_err(|e| e.nested_in(&pointer("/_embedded", &rel)))?;
+                embedded.push((rel, resources));
+            }
+        }
+
+        Ok(ResourceRef {
+            text,
+            states: members.iter().map(|_| OnceCell::new()).collect(),
+            members,
+            links,
+            embedded,
+        })
+    }
+
+    /// The json text of the resource
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Read the whole resource into a `Resource`
+    ///
+    /// Paths in errors are relative to this resource.
+    pub fn to_resource(&self) -> Result<Resource, HalError> {
+        Resource::parse(&to_json(self.text))
+    }
+
+    /// Get the state member with the given key, reading it the first time it
+    /// is asked for
+    pub fn get_state(&self, key: &str) -> Option<&HalState> {
+        self.state_member(key).map(|i| self.state_at(i))
+    }
+
+    /// Get the state member with the given key converted into a value
+    ///
+    /// A missing member is an error unless `T` has a value for it, such as
+    /// `None` for an `Option`.
+    pub fn get_state_as<T: FromHalState>(&self, key: &str) -> Result<T, HalError> {
+        match self.get_state(key) {
+            Some(state) => T::from_hal_state(state).map_err(|e| e.nested_in(&pointer("", key))),
+            None => {
+                T::from_missing().ok_or_else(|| {
+                    HalError::MissingField {
+                        path: "".to_string(),
+                        field: key.to_string(),
+                    }
+                })
+            }
+        }
+    }
+
+    /// Get the json text of the state member with the given key without
+    /// reading it
+    pub fn get_raw_state(&self, key: &str) -> Option<&'a str> {
+        self.state_member(key).map(|i| self.members[i].1)
+    }
+
+    /// Iterate over the state members in document order
+    pub fn state_entries<'b>(&'b self) -> impl Iterator<Item = (&'b str, &'b HalState)> + 'b {
+        self.members
+            .iter()
+            .zip(&self.states)
+            .filter(|((key, _), _)| !RESERVED_KEYS.contains(&&key[..]))
+            .map(|((key, text), state)| (&key[..], read_state(state, text)))
+    }
+
+    /// Get the first link with the given rel
+    pub fn get_link(&self, rel: &str) -> Option<&LinkRef<'a>> {
+        self.get_links(rel).first()
+    }
+
+    /// Get all links with the given rel
+    pub fn get_links(&self, rel: &str) -> &[LinkRef<'a>] {
+        self.links.iter().find(|(r, _)| r == rel).map_or(&[], |(_, links)| &links[..])
+    }
+
+    /// Get the link to this resource
+    pub fn self_link(&self) -> Option<&LinkRef<'a>> {
+        self.get_link("self")
+    }
+
+    /// Iterate over the rels that have links, in document order
+    pub fn link_rels<'b>(&'b self) -> impl Iterator<Item = &'b str> + 'b {
+        self.links.iter().map(|(rel, _)| &rel[..])
+    }
+
+    /// Iterate over each rel along with its links, in document order
+    pub fn links<'b>(&'b self) -> impl Iterator<Item = (&'b str, &'b [LinkRef<'a>])> + 'b {
+        self.links.iter().map(|(rel, links)| (&rel[..], &links[..]))
+    }
+
+    /// Get the first embedded resource with the given rel
+    pub fn get_embedded_resource(&self, rel: &str) -> Option<&ResourceRef<'a>> {
+        self.get_embedded(rel).first()
+    }
+
+    /// Get all embedded resources with the given rel
+    pub fn get_embedded(&self, rel: &str) -> &[ResourceRef<'a>] {
+        self.embedded.iter().find(|(r, _)| r == rel).map_or(&[], |(_, resources)| &resources[..])
+    }
+
+    /// Iterate over the rels that have embedded resources, in document order
+    pub fn embedded_rels<'b>(&'b self) -> impl Iterator<Item = &'b str> + 'b {
+        self.embedded.iter().map(|(rel, _)| &rel[..])
+    }
+
+    /// Iterate over each rel along with its embedded resources, in document
+    /// order
+    pub fn embedded<'b>(&'b self) -> impl Iterator<Item = (&'b str, &'b [ResourceRef<'a>])> + 'b {
+        self.embedded.iter().map(|(rel, resources)| (&rel[..], &resources[..]))
+    }
+
+    /// Get the curie declared with the given name
+    pub fn get_curie(&self, name: &str) -> Option<&LinkRef<'a>> {
+        self.get_links("curies").iter().find(|curie| curie.get_name() == Some(name))
+    }
+
+    /// Expand a curie rel such as `ea:order` into the URI it stands for
+    ///
+    /// Returns `None` if the rel is not a curie or its prefix is not declared
+    /// on this resource.
+    pub fn expand_rel(&self, rel: &str) -> Option<String> {
+        let (prefix, reference) = curie::split(rel)?;
+        curie::expand(&Link::from(self.get_curie(prefix)?), reference)
+    }
+
+    /// Get the HAL-FORMS template with the given key, reading only that
+    /// template
+    ///
+    /// Fails if `_templates` is not an object or the template is malformed.
+    /// As with `Resource::parse`, `_templates` is also a state member.
+    pub fn get_template(&self, key: &str) -> Result<Option<Template>, HalError> {
+        let templates = match find(&self.members, "_templates") {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let templates_path = pointer("", "_templates");
+        match find(&object_at(templates, &templates_path)?, key) {
+            Some(value) => Template::parse_at(&to_json(value), &pointer(&templates_path, key)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Where the state member with the given key is in `members`
+    fn state_member(&self, key: &str) -> Option<usize> {
+        if RESERVED_KEYS.contains(&key) {
+            return None;
+        }
+        self.members.iter().position(|(k, _)| k == key)
+    }
+
+    fn state_at(&self, i: usize) -> &HalState {
+        read_state(&self.states[i], self.members[i].1)
+    }
+}
+
+/// Views are equal when they read the same json text
+impl<'a> PartialEq for ResourceRef<'a> {
+    fn eq(&self, other: &ResourceRef<'a>) -> bool {
+        self.text == other.text
+    }
+}
+
+impl<'a> fmt::Debug for ResourceRef<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("ResourceRef").field(&self.text).finish()
+    }
+}
+
+impl<'a> LinkRef<'a> {
+    /// Reads the link in checked json text, reporting where it is malformed
+    /// relative to the link
+    fn at(text: &'a str) -> Result<LinkRef<'a>, HalError> {
+        let members = object_at(text, "")?;
+
+        let href = match find(&members, "href") {
+            Some(value) => string_at(value, "", "href")?,
+            None => {
+                return Err(HalError::MissingField {
+                    path: "".to_string(),
+                    field: "href".to_string(),
+                })
+            }
+        };
+
+        let templated = match find(&members, "templated") {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            Some(other) => {
+                return Err(HalError::WrongType {
+                    path: pointer("", "templated"),
+                    expected: "boolean",
+                    found: value_type(other),
+                })
+            }
+            None => None,
+        };
+
+        Ok(LinkRef {
+            href,
+            templated,
+            media_type: optional_string_at(&members, "", "type")?,
+            deprecation: optional_string_at(&members, "", "deprecation")?,
+            name: optional_string_at(&members, "", "name")?,
+            profile: optional_string_at(&members, "", "profile")?,
+            title: optional_string_at(&members, "", "title")?,
+            hreflang: optional_string_at(&members, "", "hreflang")?,
+        })
+    }
+
+    /// Get the target URI (or URI Template) of the link
+    pub fn get_href(&self) -> &str {
+        &self.href
+    }
+
+    /// Get whether the href is a URI Template, if the link says
+    pub fn get_templated(&self) -> Option<bool> {
+        self.templated
+    }
+
+    /// Get the media type expected when dereferencing the target
+    pub fn get_media_type(&self) -> Option<&str> {
+        self.media_type.as_deref()
+    }
+
+    /// Get the URL that explains why the link is deprecated
+    pub fn get_deprecation(&self) -> Option<&str> {
+        self.deprecation.as_deref()
+    }
+
+    /// Get the name that tells this link apart from others with the same rel
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Get the human readable title of the link
+    pub fn get_title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Get the URI of the profile of the target resource
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Get the language of the target resource
+    pub fn get_hreflang(&self) -> Option<&str> {
+        self.hreflang.as_deref()
+    }
+}
+
+impl<'a> From<LinkRef<'a>> for Link {
+    fn from(link: LinkRef<'a>) -> Link {
+        Link {
+            href: link.href.into_owned(),
+            templated: link.templated,
+            media_type: link.media_type.map(Cow::into_owned),
+            deprecation: link.deprecation.map(Cow::into_owned),
+            name: link.name.map(Cow::into_owned),
+            profile: link.profile.map(Cow::into_owned),
+            title: link.title.map(Cow::into_owned),
+            hreflang: link.hreflang.map(Cow::into_owned),
+        }
+    }
+}
+
+impl<'a, 'b> From<&'b LinkRef<'a>> for Link {
+    fn from(link: &'b LinkRef<'a>) -> Link {
+        Link::from(link.clone())
+    }
+}
+
+impl<'a> TryFrom<ResourceRef<'a>> for Resource {
+    type Error = HalError;
+
+    fn try_from(resource: ResourceRef<'a>) -> Result<Resource, HalError> {
+        resource.to_resource()
+    }
+}
+
+/// Checks that json text is a single well formed value, returning the value
+/// without the whitespace around it, or otherwise where the value, key or
+/// separator that is malformed starts
+///
+/// Open arrays and objects are tracked on a stack rather than by recursion,
+/// so deeply nested input cannot overflow the call stack.
+fn check(text: &str) -> Result<&str, usize> {
+    let bytes = text.as_bytes();
+    let start = whitespace(bytes, 0);
+    let mut i = start;
+    // Whether each open array or object is an object
+    let mut open: Vec<bool> = Vec::new();
+
+    loop {
+        i = match bytes.get(i) {
+            Some(b'{') => {
+                i = whitespace(bytes, i + 1);
+                if bytes.get(i) != Some(&b'}') {
+                    open.push(true);
+                    i = check_key(bytes, i).ok_or(i)?;
+                    continue;
+                }
+                i + 1
+            }
+            Some(b'[') => {
+                i = whitespace(bytes, i + 1);
+                if bytes.get(i) != Some(&b']') {
+                    open.push(false);
+                    continue;
+                }
+                i + 1
+            }
+            Some(b'"') => check_string(bytes, i).ok_or(i)?,
+            Some(b't') => check_literal(bytes, i, b"true").ok_or(i)?,
+            Some(b'f') => check_literal(bytes, i, b"false").ok_or(i)?,
+            Some(b'n') => check_literal(bytes, i, b"null").ok_or(i)?,
+            Some(b'-') | Some(b'0'..=b'9') => check_number(text, i).ok_or(i)?,
+            _ => return Err(i),
+        };
+
+        // Close the arrays and objects the value ends, up to the next value
+        loop {
+            i = whitespace(bytes, i);
+            let object = match open.last() {
+                Some(&object) => object,
+                None => return if i == bytes.len() { Ok(&text[start..i]) } else { Err(i) },
+            };
+            match bytes.get(i) {
+                Some(b',') => {
+                    i = whitespace(bytes, i + 1);
+                    if object {
+                        i = check_key(bytes, i).ok_or(i)?;
+                    }
+                    break;
+                }
+                Some(b'}') if object => i += 1,
+                Some(b']') if !object => i += 1,
+                _ => return Err(i),
+            }
+            open.pop();
+        }
+    }
+}
+
+/// Checks an object key and the colon after it, returning where its value
+/// starts
+fn check_key(bytes: &[u8], i: usize) -> Option<usize> {
+    if bytes.get(i) != Some(&b'"') {
+        return None;
+    }
+    let i = whitespace(bytes, check_string(bytes, i)?);
+    if bytes.get(i) != Some(&b':') {
+        return None;
+    }
+    Some(whitespace(bytes, i + 1))
+}
+
+fn check_string(bytes: &[u8], mut i: usize) -> Option<usize> {
+    i += 1;
+    loop {
+        match *bytes.get(i)? {
+            b'"' => return Some(i + 1),
+            b'\\' => {
+                i = match *bytes.get(i + 1)? {
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => i + 2,
+                    b'u' => match hex_escape(bytes, i)? {
+                        0xDC00..=0xDFFF => return None,
+                        // A leading surrogate has to be followed by a trailing one
+                        0xD800..=0xDBFF => match hex_escape(bytes, i + 6)? {
+                            0xDC00..=0xDFFF => i + 12,
+                            _ => return None,
+                        },
+                        _ => i + 6,
+                    },
+                    _ => return None,
+                }
+            }
+            0x00..=0x1F => return None,
+            _ => i += 1,
+        }
+    }
+}
+
+/// Reads the `\uXXXX` escape at `i`
+fn hex_escape(bytes: &[u8], i: usize) -> Option<u16> {
+    if bytes.get(i..i + 2)? != b"\\u" {
+        return None;
+    }
+    let digits = bytes.get(i + 2..i + 6)?;
+    if !digits.iter().all(u8::is_ascii_hexdigit) {
+        return None;
+    }
+    u16::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+}
+
+fn check_literal(bytes: &[u8], i: usize, literal: &[u8]) -> Option<usize> {
+    if bytes[i..].starts_with(literal) {
+        Some(i + literal.len())
+    } else {
+        None
+    }
+}
+
+/// Checks a number, whose whole part has to fit in a `u64` as it does for
+/// the json parser
+fn check_number(text: &str, mut i: usize) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let digits = |mut i: usize| {
+        while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        i
+    };
+
+    let negative = bytes[i] == b'-';
+    if negative {
+        i += 1;
+    }
+    let whole = i;
+    i = match bytes.get(i) {
+        Some(b'0') if !bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => i + 1,
+        Some(b'1'..=b'9') => digits(i),
+        _ => return None,
+    };
+    let whole = text[whole..i].parse::<u64>().ok()?;
+
+    let mut integer = true;
+    if bytes.get(i) == Some(&b'.') {
+        if !bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+            return None;
+        }
+        i = digits(i + 1);
+        integer = false;
+    }
+    if let Some(b'e') | Some(b'E') = bytes.get(i) {
+        i += 1;
+        if let Some(b'+') | Some(b'-') = bytes.get(i) {
+            i += 1;
+        }
+        if !bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            return None;
+        }
+        i = digits(i);
+        integer = false;
+    }
+
+    if integer && negative && whole > i64::MAX as u64 + 1 {
+        return None;
+    }
+    Some(i)
+}
+
+fn whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while let Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') = bytes.get(i) {
+        i += 1;
+    }
+    i
+}
+
+/// Reports a syntax error in text that did not check, as the json parser
+/// does
+///
+/// The parser loses its place after a trailing comma in a nested object and
+/// can read on without an error, so then the error is reported where the
+/// checks found it.
+fn syntax_error(text: &str, at: usize) -> HalError {
+    for event in Parser::new(text.chars()) {
+        if let JsonEvent::Error(e) = event {
+            return resource::syntax_error(e);
+        }
+    }
+
+    let before = &text[..at];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |i| i + 1)..].chars().count() + 1;
+    resource::syntax_error(ParserError::SyntaxError(ErrorCode::InvalidSyntax, line, column))
+}
+
+/// Finds where the value at `i` ends in checked json text
+fn skip(bytes: &[u8], mut i: usize) -> usize {
+    let mut depth = 0;
+    loop {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                i += 1;
+            }
+            b'}' | b']' => {
+                depth -= 1;
+                i += 1;
+            }
+            _ if depth == 0 => {
+                while i < bytes.len() && !b",}] \n\t\r".contains(&bytes[i]) {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+        if depth == 0 {
+            return i;
+        }
+    }
+}
+
+/// Reads the value of a rel in checked json text, which is either one item
+/// or an array of them, reporting paths relative to the value
+fn read_rel<'a, T>(text: &'a str, read: fn(&'a str) -> Result<T, HalError>) -> Result<Vec<T>, HalError> {
+    if text.starts_with('[') {
+        elements(text)
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| read(value).map_err(|e| e.nested_in(&pointer("", &i.to_string()))))
+            .collect()
+    } else {
+        Ok(vec![read(text)?])
+    }
+}
+
+/// The members of an object in checked json text
+///
+/// Only the last of duplicate keys is kept, as a json object does.
+fn members(text: &str) -> Members<'_> {
+    let bytes = text.as_bytes();
+    let mut members: Members = Vec::new();
+    let mut i = whitespace(bytes, 1);
+    while bytes[i] == b'"' {
+        let key_end = skip(bytes, i);
+        let start = whitespace(bytes, whitespace(bytes, key_end) + 1);
+        let end = skip(bytes, start);
+        members.push((decode(&text[i..key_end]), &text[start..end]));
+
+        i = whitespace(bytes, end);
+        if bytes[i] == b',' {
+            i = whitespace(bytes, i + 1);
+        }
+    }
+
+    if members.len() > 1 {
+        let mut last = BTreeMap::new();
+        for (i, (key, _)) in members.iter().enumerate() {
+            last.insert(key.clone(), i);
+        }
+        let mut i = 0;
+        members.retain(|(key, _)| {
+            i += 1;
+            last[key] == i - 1
+        });
+    }
+    members
+}
+
+/// The elements of an array in checked json text
+fn elements(text: &str) -> Vec<&str> {
+    let bytes = text.as_bytes();
+    let mut elements = Vec::new();
+    let mut i = whitespace(bytes, 1);
+    while bytes[i] != b']' {
+        let end = skip(bytes, i);
+        elements.push(&text[i..end]);
+
+        i = whitespace(bytes, end);
+        if bytes[i] == b',' {
+            i = whitespace(bytes, i + 1);
+        }
+    }
+    elements
+}
+
+/// A json string in checked text, borrowed from the text unless it has
+/// escapes
+fn decode(text: &str) -> Cow<'_, str> {
+    let inner = &text[1..text.len() - 1];
+    if !inner.contains('\\') {
+        return Cow::Borrowed(inner);
+    }
+    match to_json(text) {
+        Json::String(s) => Cow::Owned(s),
+        _ => unreachable!("the text is a json string"),
+    }
+}
+
+/// Reads a state member the first time it is asked for
+fn read_state<'s>(state: &'s OnceCell<HalState>, text: &str) -> &'s HalState {
+    state.get_or_init(|| to_json(text).to_hal_state())
+}
+
+/// Reads a value in checked json text
+fn to_json(text: &str) -> Json {
+    Json::from_str(text).expect("the text was checked when the view was read")
+}
+
+fn find<'a>(members: &[(Cow<str>, &'a str)], key: &str) -> Option<&'a str> {
+    members.iter().find(|(k, _)| k == key).map(|&(_, value)| value)
+}
+
+/// The members of an object in checked json text, or what was found instead
+fn object_at<'a>(text: &'a str, path: &str) -> Result<Members<'a>, HalError> {
+    if text.starts_with('{') {
+        Ok(members(text))
+    } else {
+        Err(HalError::WrongType {
+            path: path.to_string(),
+            expected: "object",
+            found: value_type(text),
+        })
+    }
+}
+
+fn string_at<'a>(text: &'a str, path: &str, field: &str) -> Result<Cow<'a, str>, HalError> {
+    if text.starts_with('"') {
+        Ok(decode(text))
+    } else {
+        Err(HalError::WrongType {
+            path: pointer(path, field),
+            expected: "string",
+            found: value_type(text),
+        })
+    }
+}
+
+fn optional_string_at<'a>(members: &[(Cow<str>, &'a str)], path: &str, field: &str)
+                          -> Result<Option<Cow<'a, str>>, HalError> {
+    match find(members, field) {
+        Some(value) => string_at(value, path, field).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Names the json type of a value in checked text for use in error messages
+fn value_type(text: &str) -> &'static str {
+    match text.as_bytes()[0] {
+        b'{' => "object",
+        b'[' => "array",
+        b'"' => "string",
+        b't' | b'f' => "boolean",
+        b'n' => "null",
+        _ => "number",
+    }
+}
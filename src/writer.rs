@@ -0,0 +1,85 @@
+//! Writes Hal documents straight to an `io::Write`.
+//!
+//! `Resource::to_json` builds a whole `Json` tree before anything can be
+//! written. These functions write the document as they walk the resource
+//! instead, producing the same bytes as `to_string()` and `format!("{:#}")`
+//! on the resource. Those match `to_json().to_string()` and
+//! `to_json().pretty().to_string()` unless the resource preserves order.
+//!
+//! Each piece of the document is written as it is produced, so wrap
+//! unbuffered writers such as files and sockets in an `io::BufWriter`.
+
+use std::fmt;
+use std::io::{self, Write};
+use serialize::Encodable;
+use serialize::json::{Encoder, EncoderError};
+
+use resource::Resource;
+use streaming::StreamingResource;
+
+/// Writes a resource as compact json
+pub fn to_writer<W: Write>(writer: W, resource: &Resource) -> io::Result<()> {
+    write(writer, false, |e| resource.encode(e))
+}
+
+/// Writes a resource as json indented like `Json::pretty`
+pub fn to_writer_pretty<W: Write>(writer: W, resource: &Resource) -> io::Result<()> {
+    write(writer, true, |e| resource.encode(e))
+}
+
+/// Writes a streaming resource as compact json, consuming its lazy resources
+/// as they are written
+pub fn stream<W: Write>(writer: W, resource: StreamingResource<'_>) -> io::Result<()> {
+    write(writer, false, |e| resource.encode(e))
+}
+
+/// Writes a streaming resource as json indented like `Json::pretty`,
+/// consuming its lazy resources as they are written
+pub fn stream_pretty<W: Write>(writer: W, resource: StreamingResource<'_>) -> io::Result<()> {
+    write(writer, true, |e| resource.encode(e))
+}
+
+fn write<W, F>(writer: W, pretty: bool, encode: F) -> io::Result<()>
+    where W: Write,
+          F: FnOnce(&mut Encoder) -> Result<(), EncoderError>
+{
+    let mut adapter = Adapter { writer, error: None };
+    let result = if pretty {
+        encode(&mut Encoder::new_pretty(&mut adapter))
+    } else {
+        encode(&mut Encoder::new(&mut adapter))
+    };
+    adapter.finish(result)
+}
+
+/// Lets the json encoder, which writes to a `fmt::Write`, write to an
+/// `io::Write`, keeping the io error that stopped it
+struct Adapter<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Adapter<W> {
+    // `io::Error::other` needs Rust 1.74
+    #[allow(clippy::io_other_error)]
+    fn finish(self, result: Result<(), EncoderError>) -> io::Result<()> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(EncoderError::FmtError(_)) => {
+                Err(self.error.unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, "json formatting failed")))
+            }
+            Err(EncoderError::BadHashmapKey) => {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "json object keys must be strings"))
+            }
+        }
+    }
+}
+
+impl<W: Write> fmt::Write for Adapter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
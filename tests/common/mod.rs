@@ -0,0 +1,34 @@
+//! Fixtures shared by the integration tests
+
+use hal::link::Link;
+use hal::resource::Resource;
+
+/// The example document from the Hal specification, with a few more link
+/// members and values that need escaping
+pub fn spec() -> Resource {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("next", &Link::new("/orders?page=2"))
+        .add_link("ea:find", Link::new("/orders{?id}").title("Find \"an\" order"))
+        .add_link("ea:admin", Link::new("/admins/2").title("Fred").deprecation("http://example.com/deprecated"))
+        .add_link("ea:admin", Link::new("/admins/5").title("Kate").hreflang("en").media_type("text/html"))
+        .add_state("currentlyProcessing", 14i64)
+        .add_state("shippedToday", 20i64)
+        .set_embedded("ea:customer", &Resource::with_self("/customers/7809"))
+        .add_resource("ea:order",
+            Resource::with_self("/orders/123")
+                .add_link("ea:basket", Link::new("/baskets/98712").name("basket").profile("http://example.com/basket"))
+                .add_state("total", 30.00_f64)
+                .add_state("currency", "USD")
+                .add_state("status", "shipped")
+        )
+        .add_resource("ea:order",
+            Resource::with_self("/orders/124")
+                .add_state("total", 20.25_f64)
+                .add_state("currency", "USD")
+                .add_state("status", ())
+                .add_state("note", "Leave at the\ndoor")
+                .add_state("tags", Vec::<String>::new())
+        );
+    hal
+}
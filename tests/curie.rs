@@ -0,0 +1,138 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::curie::{CurieIssue, CurieIssueKind};
+use hal::link::Link;
+use hal::resource::Resource;
+use serialize::json::ToJson;
+
+#[test]
+fn expand_rel() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_curie("acme", "http://acme.com/rels/{rel}.html");
+
+    assert_eq!(hal.expand_rel("ea:order"), Some("http://example.com/docs/rels/order".to_string()));
+    assert_eq!(hal.expand_rel("acme:widget"), Some("http://acme.com/rels/widget.html".to_string()));
+    assert_eq!(hal.expand_rel("xx:order"), None);
+    assert_eq!(hal.expand_rel("next"), None);
+    assert_eq!(hal.expand_rel("http://example.com/docs/rels/order"), None);
+    assert_eq!(hal.get_curie("ea").map(|curie| curie.get_href()),
+               Some("http://example.com/docs/rels/{rel}"));
+}
+
+#[test]
+fn compact_rel() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_curie("acme", "http://acme.com/rels/{rel}.html");
+
+    assert_eq!(hal.compact_rel("http://example.com/docs/rels/order"), Some("ea:order".to_string()));
+    assert_eq!(hal.compact_rel("http://acme.com/rels/widget.html"), Some("acme:widget".to_string()));
+    assert_eq!(hal.compact_rel("http://acme.com/rels/widget"), None);
+    assert_eq!(hal.compact_rel("http://example.com/docs/rels/"), None);
+    assert_eq!(hal.compact_rel("http://example.org/rels/order"), None);
+}
+
+#[test]
+fn compact_rels_when_added() {
+    let mut hal = Resource::with_self("/orders");
+    hal.compact_rels(true)
+        .add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("http://example.com/docs/rels/admin", &Link::new("/admins/2"))
+        .add_link("http://example.org/rels/other", &Link::new("/other"))
+        .add_resource("http://example.com/docs/rels/order", &Resource::with_self("/orders/123"));
+
+    let output = r#"{"_embedded":{"ea:order":[{"_links":{"self":{"href":"/orders/123"}}}]},"_links":{"curies":[{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true}],"ea:admin":{"href":"/admins/2"},"http://example.org/rels/other":{"href":"/other"},"self":{"href":"/orders"}}}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+}
+
+#[test]
+fn compact_rels_when_set() {
+    let mut hal = Resource::with_self("/orders");
+    hal.compact_rels(true)
+        .add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:admin", &Link::new("/admins/2"))
+        .set_links("http://example.com/docs/rels/admin", vec![Link::new("/admins/5")])
+        .add_resource("ea:order", &Resource::with_self("/orders/123"))
+        .set_resources("http://example.com/docs/rels/order", vec![Resource::with_self("/orders/124")]);
+
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["curies", "ea:admin", "self"]);
+    assert_eq!(hal.get_link("ea:admin").map(|link| link.get_href()), Some("/admins/5"));
+    assert_eq!(hal.embedded_rels().collect::<Vec<_>>(), vec!["ea:order"]);
+
+    hal.set_links("http://example.com/docs/rels/admin", vec![])
+        .set_resources("http://example.com/docs/rels/order", vec![]);
+    assert!(hal.get_link("ea:admin").is_none());
+    assert_eq!(hal.embedded_rels().count(), 0);
+}
+
+#[test]
+fn compact_rels_when_looked_up() {
+    let mut hal = Resource::with_self("/orders");
+    hal.compact_rels(true)
+        .add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:admin", &Link::new("/admins/2"))
+        .add_resource("ea:order", &Resource::with_self("/orders/123"))
+        .links_as_array("http://example.com/docs/rels/admin", true);
+
+    assert_eq!(hal.get_link("http://example.com/docs/rels/admin").map(|link| link.get_href()), Some("/admins/2"));
+    assert_eq!(hal.get_links("http://example.com/docs/rels/admin").len(), 1);
+    assert_eq!(hal.get_embedded("http://example.com/docs/rels/order").len(), 1);
+    assert!(hal.get_embedded_resource("http://example.com/docs/rels/order").is_some());
+    assert!(hal.to_json().to_string().contains(r#""ea:admin":[{"href":"/admins/2"}]"#));
+
+    assert_eq!(hal.remove_link("http://example.com/docs/rels/admin").map(|links| links.len()), Some(1));
+    assert_eq!(hal.remove_embedded("http://example.com/docs/rels/order").map(|resources| resources.len()), Some(1));
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["curies", "self"]);
+    assert_eq!(hal.embedded_rels().count(), 0);
+}
+
+#[test]
+fn validate_curies() {
+    let mut order = Resource::with_self("/orders/123");
+    order.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:basket", &Link::new("/baskets/98712"));
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:admin", &Link::new("/admins/2"))
+        .add_link("next", &Link::new("/orders?page=2"))
+        .add_link("http://example.org/rels/other", &Link::new("/other"))
+        .add_resource("ea:order", &order);
+
+    assert_eq!(hal.validate_curies(), vec![]);
+}
+
+#[test]
+fn validate_curies_reports_issues() {
+    let mut order = Resource::with_self("/orders/123");
+    order.add_curie("ea", "http://example.org/rels/{rel}")
+        .add_link("acme:widget", &Link::new("/widgets/1"));
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("curies", Link::new("http://example.com/docs/rels/{rel}").name("ea"))
+        .add_link("curies", Link::new("http://example.com/docs/rels/").name("nr").templated(false))
+        .add_link("curies", &Link::new("http://example.com/docs/{rel}"))
+        .add_link("xx:admin", &Link::new("/admins/2"))
+        .add_resource("ea:order", &order);
+
+    let issue = |path: &str, kind| CurieIssue { path: path.to_string(), kind };
+    assert_eq!(hal.validate_curies(), vec![
+        issue("/_links/curies/1", CurieIssueKind::DuplicateName("ea".to_string())),
+        issue("/_links/curies/2", CurieIssueKind::NotTemplated),
+        issue("/_links/curies/2", CurieIssueKind::MissingRelVariable),
+        issue("/_links/curies/3", CurieIssueKind::MissingName),
+        issue("/_links/xx:admin", CurieIssueKind::UndeclaredPrefix("xx".to_string())),
+        issue("/_embedded/ea:order/0/_links/curies/0", CurieIssueKind::Redefined {
+            name: "ea".to_string(),
+            href: "http://example.org/rels/{rel}".to_string(),
+            previous: "http://example.com/docs/rels/{rel}".to_string(),
+        }),
+        issue("/_embedded/ea:order/0/_links/acme:widget",
+              CurieIssueKind::UndeclaredPrefix("acme".to_string())),
+    ]);
+    assert_eq!(hal.validate_curies()[4].to_string(),
+               "curie prefix `xx` is not declared at `/_links/xx:admin`");
+}
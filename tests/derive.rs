@@ -0,0 +1,146 @@
+extern crate hal;
+extern crate hal_derive;
+extern crate rustc_serialize as serialize;
+
+// Imported under `_` so the derive from hal_derive is used even when the
+// `derive` feature re-exports it next to the trait
+use hal::{FromHal as _, ToHal as _};
+use hal_derive::{FromHal, ToHal};
+use hal::error::HalError;
+use hal::link::Link;
+use hal::resource::Resource;
+use serialize::json::ToJson;
+
+#[derive(ToHal)]
+#[hal(self = "/orders/{id}")]
+struct Order {
+    #[hal(skip)]
+    id: u64,
+    total: f64,
+    #[hal(rename = "currencyCode")]
+    currency: String,
+    status: &'static str,
+    #[hal(link = "ea:basket")]
+    basket: String,
+    #[hal(link = "ea:customer")]
+    customer: Option<Link>,
+    #[hal(link = "ea:admin")]
+    admins: Vec<&'static str>,
+    #[hal(embed = "ea:item")]
+    items: Vec<Item>,
+    #[hal(embed = "ea:voucher")]
+    voucher: Option<Item>,
+}
+
+#[derive(ToHal)]
+#[hal(self = "/orders/{order_id}/items/{{{sku}}}")]
+struct Item {
+    #[hal(skip)]
+    order_id: u64,
+    sku: &'static str,
+}
+
+#[derive(ToHal)]
+struct Total {
+    total: f64,
+}
+
+#[test]
+fn derive_to_hal() {
+    let order = Order {
+        id: 123,
+        total: 30.00,
+        currency: "USD".to_string(),
+        status: "shipped",
+        basket: "/baskets/98712".to_string(),
+        customer: Some(Link::new("/customers/7809").title("Fred").clone()),
+        admins: vec!["/admins/2", "/admins/5"],
+        items: vec![Item { order_id: 123, sku: "a1" }, Item { order_id: 123, sku: "b2" }],
+        voucher: None,
+    };
+
+    let mut hal = Resource::with_self("/orders/123");
+    hal.add_state("total", 30.00_f64)
+        .add_state("currencyCode", "USD")
+        .add_state("status", "shipped")
+        .add_link("ea:basket", &Link::new("/baskets/98712"))
+        .add_link("ea:customer", Link::new("/customers/7809").title("Fred"))
+        .add_link("ea:admin", &Link::new("/admins/2"))
+        .add_link("ea:admin", &Link::new("/admins/5"))
+        .add_resource("ea:item", Resource::with_self("/orders/123/items/{a1}").add_state("sku", "a1"))
+        .add_resource("ea:item", Resource::with_self("/orders/123/items/{b2}").add_state("sku", "b2"));
+
+    assert_eq!(order.to_hal(), hal);
+}
+
+#[test]
+fn derive_to_hal_without_self() {
+    let output = r#"{"total":20.0}"#;
+    assert_eq!(Total { total: 20.00 }.to_hal().to_json().to_string(), output);
+}
+
+#[derive(ToHal, FromHal, PartialEq, Debug)]
+#[hal(self = "/customers/{id}")]
+struct Customer {
+    #[hal(skip)]
+    id: u64,
+    name: String,
+    #[hal(rename = "emailAddress")]
+    email: Option<String>,
+    tags: Vec<String>,
+    #[hal(link = "ea:orders")]
+    orders: String,
+    #[hal(link = "ea:friend")]
+    friends: Vec<Link>,
+    #[hal(link = "ea:avatar")]
+    avatar: Option<String>,
+    #[hal(embed = "ea:address")]
+    address: Address,
+    #[hal(embed = "ea:previous")]
+    previous: Vec<Address>,
+}
+
+#[derive(ToHal, FromHal, PartialEq, Debug)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+fn customer() -> Customer {
+    Customer {
+        id: 0,
+        name: "Fred".to_string(),
+        email: None,
+        tags: vec!["vip".to_string()],
+        orders: "/customers/0/orders".to_string(),
+        friends: vec![Link::new("/customers/1"), Link::new("/customers/2")],
+        avatar: Some("/avatars/fred.png".to_string()),
+        address: Address { city: "Portland".to_string(), zip: 97201 },
+        previous: vec![Address { city: "Seattle".to_string(), zip: 98101 }],
+    }
+}
+
+#[test]
+fn derive_from_hal() {
+    let hal = customer().to_hal();
+    assert_eq!(Customer::from_hal(&hal), Ok(customer()));
+}
+
+#[test]
+fn derive_from_hal_errors() {
+    let mut hal = customer().to_hal();
+    hal.add_state("name", 7i64);
+    assert_eq!(Customer::from_hal(&hal).unwrap_err().to_string(),
+               "expected string at `/name`, found number");
+
+    let mut hal = customer().to_hal();
+    hal.add_resource("ea:previous", Resource::new().add_state("city", "Tacoma").add_state("zip", -1i64));
+    assert_eq!(Customer::from_hal(&hal).unwrap_err().to_string(),
+               "expected u32 at `/_embedded/ea:previous/1/zip`, found number");
+
+    let hal = Resource::new();
+    assert_eq!(Address::from_hal(&hal).unwrap_err().to_string(),
+               "missing field `city` at ``");
+    assert_eq!(Customer::from_hal(Resource::new().add_state("name", "Fred").add_state("tags", vec!["vip"])),
+               Err(HalError::MissingField { path: "/_links".to_string(), field: "ea:orders".to_string() }));
+}
@@ -0,0 +1,267 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::error::HalError;
+use hal::forms::{Choice, Options, Property, Template, Violation, ViolationKind};
+use hal::link::Link;
+use hal::resource::{ParseOptions, Resource};
+use hal::state::ToHalState;
+use hal::writer;
+use serialize::json::{Json, ToJson};
+
+fn order() -> Resource {
+    let mut hal = Resource::with_self("/orders/123");
+    hal.add_state("status", "processing")
+        .add_template("default", Template::new("PUT")
+            .title("Update the order")
+            .property(Property::new("status")
+                .prompt("Status")
+                .required(true)
+                .options(vec![Choice::new("processing"), Choice::new("shipped").prompt("Shipped").clone()]))
+            .property(Property::new("total").input_type("number").min(0.0).max(1000.0).step(0.01))
+            .property(Property::new("reference").regex("^[A-Z]{3}-[0-9]+$").min_length(5).max_length(12)))
+        .add_template("delete", Template::new("DELETE").target("/orders/123/cancel"));
+    hal
+}
+
+#[test]
+fn template_to_json() {
+    let output = concat!(r#"{"_links":{"self":{"href":"/orders/123"}},"#,
+                         r#""_templates":{"#,
+                         r#""default":{"method":"PUT","properties":["#,
+                         r#"{"name":"status","options":{"inline":["processing",{"prompt":"Shipped","value":"shipped"}]},"prompt":"Status","required":true},"#,
+                         r#"{"max":1000.0,"min":0.0,"name":"total","step":0.01,"type":"number"},"#,
+                         r#"{"maxLength":12,"minLength":5,"name":"reference","regex":"^[A-Z]{3}-[0-9]+$"}"#,
+                         r#"],"title":"Update the order"},"#,
+                         r#""delete":{"method":"DELETE","target":"/orders/123/cancel"}},"#,
+                         r#""status":"processing"}"#);
+    assert_eq!(order().to_json().to_string(), output);
+    assert_eq!(serialize::json::encode(&order()).unwrap(), output);
+
+    let mut out = Vec::new();
+    writer::to_writer(&mut out, &order()).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), output);
+}
+
+#[test]
+fn template_plain_hal_unchanged() {
+    let mut hal = order();
+    assert!(hal.remove_template("default").is_some());
+    assert!(hal.remove_template("delete").is_some());
+    assert_eq!(hal.to_json().to_string(),
+               r#"{"_links":{"self":{"href":"/orders/123"}},"status":"processing"}"#);
+}
+
+#[test]
+fn template_parse() {
+    let hal = order();
+    let parsed = Resource::parse_with(&hal.to_string(), ParseOptions::new().forms(true)).unwrap();
+    assert_eq!(parsed, hal);
+
+    let template = parsed.get_template("default").unwrap();
+    assert_eq!(template.get_method(), "PUT");
+    assert_eq!(template.get_content_type(), None);
+    assert_eq!(template.get_properties().len(), 3);
+
+    let status = template.get_property("status").unwrap();
+    assert_eq!(status.get_prompt(), Some("Status"));
+    assert_eq!(status.get_required(), Some(true));
+    let options = status.get_options().unwrap();
+    assert_eq!(options[1].get_value(), "shipped");
+    assert_eq!(options[1].get_prompt(), Some("Shipped"));
+
+    let total = template.get_property("total").unwrap();
+    assert_eq!((total.get_min(), total.get_max()), (Some(0.0), Some(1000.0)));
+    assert_eq!(template.get_property("reference").and_then(|p| p.get_max_length()), Some(12));
+
+    let keys: Vec<&str> = parsed.templates().map(|(key, _)| key).collect();
+    assert_eq!(keys, vec!["default", "delete"]);
+}
+
+#[test]
+fn template_parse_ordered() {
+    let input = r#"{"_templates":{"default":{"method":"POST"}},"_links":{"self":{"href":"/orders"}}}"#;
+    let hal = Resource::parse_with(input, ParseOptions::new().forms(true).preserve_order(true)).unwrap();
+    assert_eq!(serialize::json::encode(&hal).unwrap(), input);
+    assert!(hal.get_template("default").is_some());
+}
+
+#[test]
+fn template_plain_hal_state() {
+    let input = r#"{"_links":{"self":{"href":"/orders"}},"_templates":{"default":"not a template"},"total":1}"#;
+    let json = Json::from_str(input).unwrap();
+
+    let hal = Resource::parse(&json).unwrap();
+    assert!(hal.get_template("default").is_none());
+    assert_eq!(hal.get_state("_templates"), Some(&json["_templates"].to_hal_state()));
+    assert_eq!(hal.to_json(), json);
+    assert_eq!(hal.to_string(), input);
+    assert_eq!(Resource::parse_with(input, ParseOptions::new().preserve_order(true)).unwrap().to_string(), input);
+
+    assert_eq!(Resource::parse_with(input, ParseOptions::new().forms(true)).unwrap_err(),
+               HalError::WrongType { path: "/_templates/default".to_string(), expected: "object", found: "string" });
+
+    let mut hal = hal;
+    hal.add_template("default", Template::new("POST"));
+    assert_eq!(hal.get_state("_templates"), None);
+    assert!(hal.to_string().contains(r#""_templates":{"default":{"method":"POST"}}"#));
+}
+
+#[test]
+fn template_parse_errors() {
+    let parse = |input: &str| Resource::parse_with(input, ParseOptions::new().forms(true));
+
+    assert_eq!(parse(r#"{"_templates":{"default":{"title":"No method"}}}"#),
+               Err(HalError::MissingField { path: "/_templates/default".to_string(), field: "method".to_string() }));
+    assert_eq!(parse(r#"{"_templates":{"default":{"method":"POST","properties":[{"name":"total","min":"0"}]}}}"#),
+               Err(HalError::WrongType {
+                   path: "/_templates/default/properties/0/min".to_string(),
+                   expected: "number",
+                   found: "string",
+               }));
+    assert_eq!(parse(r#"{"_templates":{"default":{"method":"POST","properties":[{"prompt":"Total"}]}}}"#),
+               Err(HalError::MissingField {
+                   path: "/_templates/default/properties/0".to_string(),
+                   field: "name".to_string(),
+               }));
+    assert_eq!(parse(r#"{"_templates":[]}"#),
+               Err(HalError::WrongType { path: "/_templates".to_string(), expected: "object", found: "array" }));
+}
+
+#[test]
+fn template_reserved_key() {
+    // Plain Hal does not reserve the key
+    let mut hal = Resource::new();
+    assert!(hal.try_add_state("_templates", 1i64).is_ok());
+    assert_eq!(hal.get_state("_templates"), Some(&1i64.to_hal_state()));
+
+    hal.add_template("default", Template::new("POST"));
+    assert_eq!(hal.try_add_state("_templates", 1i64).err(),
+               Some(HalError::ReservedKey("_templates".to_string())));
+    assert!(hal.remove_template("default").is_some());
+    assert!(hal.try_add_state("_templates", 1i64).is_ok());
+}
+
+fn users() -> Template {
+    let mut roles = Options::link(Link::new("/roles{?q}"));
+    roles.prompt_field("title").value_field("id").selected_value("2").min_items(1).max_items(2);
+
+    let mut colour = Options::inline(vec![Choice::new("r").prompt("Red").clone(), Choice::new("g").prompt("Green").clone()]);
+    colour.prompt_field("label").value_field("code").max_items(1);
+
+    let mut template = Template::new("POST");
+    template.property(Property::new("name").required(true).min_length(2).max_length(8))
+        .property(Property::new("age").input_type("number").min(18.0).max(130.0))
+        .property(Property::new("id").read_only(true).value("42"))
+        .property(Property::new("code").regex("[A-Z]{3}"))
+        .property(Property::new("roles").options(roles))
+        .property(Property::new("colour").options(colour))
+        .property(Property::new("tags").options(vec!["a", "b", "c"]));
+    template
+}
+
+#[test]
+fn option_sources() {
+    let template = users();
+    let output = concat!(r#"{"name":"roles","options":{"link":{"href":"/roles{?q}","templated":true},"#,
+                         r#""maxItems":2,"minItems":1,"promptField":"title","selectedValues":["2"],"valueField":"id"}}"#);
+    assert_eq!(template.get_property("roles").unwrap().to_json().to_string(), output);
+
+    let output = concat!(r#"{"name":"colour","options":{"inline":[{"code":"r","label":"Red"},{"code":"g","label":"Green"}],"#,
+                         r#""maxItems":1,"promptField":"label","valueField":"code"}}"#);
+    assert_eq!(template.get_property("colour").unwrap().to_json().to_string(), output);
+
+    assert_eq!(Template::parse(&template.to_json()), Ok(template.clone()));
+
+    let roles = template.get_property("roles").and_then(|p| p.get_options_element()).unwrap();
+    assert_eq!(roles.get_link().map(|link| link.get_href()), Some("/roles{?q}"));
+    assert!(roles.get_inline().is_none());
+    assert!(template.get_property("roles").unwrap().get_options().is_none());
+    assert_eq!(roles.get_selected_values(), &["2".to_string()]);
+    assert!(roles.is_multiple());
+    assert!(template.get_property("roles").unwrap().is_required());
+
+    let fetched = Json::from_str(r#"[{"id":"1","title":"Admin"},"guest"]"#).unwrap();
+    let choices = roles.read_linked(&fetched).unwrap();
+    assert_eq!(choices, vec![Choice::new("1").prompt("Admin").clone(), Choice::new("guest")]);
+    assert_eq!(roles.read_linked(&Json::from_str(r#"[{"title":"Admin"}]"#).unwrap()),
+               Err(HalError::MissingField { path: "/0".to_string(), field: "id".to_string() }));
+
+    let colour = template.get_property("colour").and_then(|p| p.get_options_element()).unwrap();
+    assert!(!colour.is_multiple());
+    assert_eq!(colour.get_inline().map(|choices| choices[0].get_prompt()), Some(Some("Red")));
+
+    let input = r#"{"name":"roles","options":{"selectedValues":["2"]}}"#;
+    assert_eq!(Property::parse(&Json::from_str(input).unwrap()),
+               Err(HalError::MissingField { path: "/options".to_string(), field: "inline".to_string() }));
+}
+
+#[test]
+fn validate_payload() {
+    let template = users();
+    let valid = Json::from_str(r#"{"name":"Ada","age":36,"id":"42","roles":["1","2"],"colour":"g","tags":["a","c"]}"#).unwrap();
+    assert_eq!(template.validate(&valid), vec![]);
+
+    let invalid = Json::from_str(r#"{"name":"A","age":"old","id":"7","roles":["1","2","3"],"colour":["r","g"],"tags":["d"]}"#).unwrap();
+    let violations: Vec<String> = template.validate(&invalid).iter().map(|v| v.to_string()).collect();
+    assert_eq!(violations, vec![
+        "value is shorter than 2 characters at `/name`",
+        "expected number at `/age`",
+        "value is read-only at `/id`",
+        "more than 2 values are selected at `/roles`",
+        "more than 1 values are selected at `/colour`",
+        "`d` is not one of the options at `/tags/0`",
+    ]);
+
+    let violations = template.validate(&Json::from_str(r#"{"age":12,"colour":"b"}"#).unwrap());
+    assert_eq!(violations.into_iter().map(|v| (v.path, v.kind)).collect::<Vec<_>>(), vec![
+        ("/name".to_string(), ViolationKind::Missing),
+        ("/age".to_string(), ViolationKind::BelowMin { min: 18.0 }),
+        ("/roles".to_string(), ViolationKind::Missing),
+        ("/colour".to_string(), ViolationKind::NotAnOption { value: "b".to_string() }),
+    ]);
+
+    assert_eq!(template.validate(&Json::from_str("[]").unwrap()),
+               vec![Violation { path: "".to_string(), kind: ViolationKind::NotAnObject }]);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn validate_regex() {
+    let template = users();
+    let payload = |code: &str| {
+        let mut payload = Json::from_str(r#"{"name":"Ada","roles":["1"]}"#).unwrap();
+        if let Json::Object(ref mut object) = payload {
+            object.insert("code".to_string(), Json::String(code.to_string()));
+        }
+        payload
+    };
+
+    assert_eq!(template.validate(&payload("ABC")), vec![]);
+    assert_eq!(template.validate(&payload("ABCD")),
+               vec![Violation { path: "/code".to_string(), kind: ViolationKind::NoMatch { regex: "[A-Z]{3}".to_string() } }]);
+}
+
+#[cfg(not(feature = "regex"))]
+#[test]
+fn validate_regex_unchecked() {
+    let template = users();
+    let payload = Json::from_str(r#"{"name":"Ada","roles":["1"],"code":"ABC"}"#).unwrap();
+    assert_eq!(template.validate(&payload),
+               vec![Violation { path: "/code".to_string(), kind: ViolationKind::Unchecked { regex: "[A-Z]{3}".to_string() } }]);
+}
+
+#[test]
+fn validate_single_item() {
+    let mut roles = Options::inline(vec!["a", "b", "c"]);
+    roles.min_items(2);
+    let mut template = Template::new("POST");
+    template.property(Property::new("roles").options(roles))
+        .property(Property::new("colour").options(Options::inline(vec!["r", "g"]).max_items(0).clone()));
+
+    let violations = template.validate(&Json::from_str(r#"{"roles":"a","colour":"r"}"#).unwrap());
+    assert_eq!(violations, vec![
+        Violation { path: "/roles".to_string(), kind: ViolationKind::TooFewItems { min_items: 2 } },
+        Violation { path: "/colour".to_string(), kind: ViolationKind::TooManyItems { max_items: 0 } },
+    ]);
+}
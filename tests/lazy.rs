@@ -0,0 +1,117 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::ToHal;
+use hal::link::Link;
+use hal::resource::Resource;
+use hal::streaming::StreamingResource;
+use hal::writer;
+use serialize::json::ToJson;
+use std::cell::Cell;
+
+struct Order {
+    id: u32,
+}
+
+impl ToHal for Order {
+    fn to_hal(self) -> Resource {
+        let mut hal = Resource::with_self(format!("/orders/{}", self.id));
+        hal.add_link_owned("ea:basket", Link::new(format!("/baskets/{}", self.id)))
+            .add_state("total", self.id as f64 * 10.0);
+        hal
+    }
+}
+
+impl ToHal for &Order {
+    fn to_hal(self) -> Resource {
+        Order { id: self.id }.to_hal()
+    }
+}
+
+fn orders(ids: Vec<u32>) -> StreamingResource<'static> {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_resource_owned("ea:order", Order { id: 1 }.to_hal())
+        .add_state("total", 3i64);
+    let mut hal = hal.into_streaming();
+    hal.add_lazy_resources("ea:order", ids.into_iter().map(|id| Order { id }));
+    hal
+}
+
+#[test]
+fn lazy_into_json() {
+    let mut eager = Resource::with_self("/orders");
+    eager.add_resource_owned("ea:order", Order { id: 1 }.to_hal())
+        .add_resource_owned("ea:order", Order { id: 2 }.to_hal())
+        .add_resource_owned("ea:order", Order { id: 3 }.to_hal())
+        .add_state("total", 3i64);
+
+    assert_eq!(orders(vec![2, 3]).into_json(), eager.to_json());
+}
+
+#[test]
+fn lazy_stream() {
+    let mut out = Vec::new();
+    writer::stream(&mut out, orders(vec![2, 3])).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), orders(vec![2, 3]).into_json().to_string());
+
+    let mut out = Vec::new();
+    writer::stream_pretty(&mut out, orders(vec![2, 3])).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), orders(vec![2, 3]).into_json().pretty().to_string());
+}
+
+#[test]
+fn lazy_empty() {
+    let mut hal = Resource::with_self("/orders").into_streaming();
+    hal.add_lazy_resources("ea:order", Vec::<Order>::new());
+    assert_eq!(hal.resource().to_json().to_string(), r#"{"_links":{"self":{"href":"/orders"}}}"#);
+
+    let mut out = Vec::new();
+    writer::stream(&mut out, hal).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), r#"{"_embedded":{"ea:order":[]},"_links":{"self":{"href":"/orders"}}}"#);
+}
+
+#[test]
+fn lazy_inherits_array_rels() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_array_rel("ea:basket");
+    let mut hal = hal.into_streaming();
+    hal.add_lazy_resources("ea:order", vec![Order { id: 1 }]);
+
+    let json = hal.into_json().to_string();
+    assert!(json.contains(r#""ea:basket":[{"href":"/baskets/1"}]"#));
+}
+
+#[test]
+fn lazy_consumed_when_written() {
+    let count = Cell::new(0);
+    let items = (1..4).map(|id| {
+        count.set(count.get() + 1);
+        Order { id }
+    });
+
+    let mut hal = Resource::with_self("/orders").into_streaming();
+    hal.add_lazy_resources("ea:order", items);
+    assert_eq!(count.get(), 0);
+    assert!(hal.resource().get_embedded("ea:order").is_empty());
+    assert_eq!(hal.resource().to_string(), r#"{"_links":{"self":{"href":"/orders"}}}"#);
+    assert_eq!(count.get(), 0);
+
+    let mut out = Vec::new();
+    writer::stream(&mut out, hal).unwrap();
+    assert_eq!(count.get(), 3);
+    assert!(String::from_utf8(out).unwrap().contains("/orders/3"));
+}
+
+#[test]
+fn lazy_borrowed() {
+    let rows = [Order { id: 2 }, Order { id: 3 }];
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_resource_owned("ea:order", Order { id: 1 }.to_hal())
+        .add_state("total", 3i64);
+    let mut hal = hal.into_streaming();
+    hal.add_lazy_resources("ea:order", rows.iter());
+
+    assert_eq!(hal.into_json(), orders(vec![2, 3]).into_json());
+    assert_eq!(rows.len(), 2);
+}
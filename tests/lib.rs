@@ -2,13 +2,15 @@ extern crate hal;
 extern crate rustc_serialize as serialize;
 
 use hal::ToHal;
-use hal::resource::Resource;
+use hal::error::HalError;
+use hal::resource::{ParseOptions, Resource};
 use hal::link::Link;
 use hal::state::ToHalState;
 use hal::state::HalState::{I64, Null};
-use serialize::json::Json;
+use serialize::json::{self, Json};
 use serialize::json::ToJson;
 use std::collections::{HashMap, BTreeMap};
+use std::convert::TryFrom;
 
 struct Order {
     total: f64,
@@ -35,6 +37,7 @@ fn link_new() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn link_from_json() {
     let json_str = r#"{"deprecation":"https://www.example.com/newer","href":"https://www.example.com","hreflang":"en","name":"example","profile":"http://tools.ietf.org/html/draft-wilde-profile-link-04","templated":true,"title":"An example link","type":"text/html"}"#;
 
@@ -90,6 +93,7 @@ fn hal_with_self() {
 }
 
 #[test]
+#[allow(deprecated)]
 fn hal_from_json() {
     let mut hal = Resource::with_self("https://www.example.com");
     hal.add_state("currentlyProcessing", 14i64)
@@ -129,6 +133,7 @@ fn hal_and_add_curie() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_cast)]
 fn hal_add_state() {
     let mut hal = Resource::new();
     hal.add_state("currentlyProcessing", 14 as i64)
@@ -141,6 +146,7 @@ fn hal_add_state() {
 }
 
 #[test]
+#[allow(unused_parens, clippy::unnecessary_cast, clippy::needless_borrow)]
 fn hal_spec() {
     let mut hal = Resource::with_self("/orders");
     hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
@@ -172,6 +178,7 @@ fn hal_spec() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_cast)]
 fn order_to_hal() {
     let order = Order { total: 20.00 as f64, currency: "USD".to_string(), status: "processing".to_string() };
 
@@ -217,3 +224,440 @@ fn option_to_hal_state() {
     assert_eq!(Some(15i64).to_hal_state(), I64(15));
     assert_eq!(None::<isize>.to_hal_state(), Null);
 }
+
+#[test]
+fn hal_parse() {
+    let json_str = r#"{"_links":{"self":{"href":"https://www.example.com"}},"currency":"USD","total":20.0}"#;
+    let json = Json::from_str(json_str).unwrap();
+
+    let mut hal = Resource::with_self("https://www.example.com");
+    hal.add_state("currency", "USD")
+        .add_state("total", 20.00_f64);
+
+    assert_eq!(Ok(hal.clone()), Resource::parse(&json));
+    assert_eq!(Ok(hal), Resource::try_from(json));
+}
+
+#[test]
+fn hal_parse_errors() {
+    let parse = |s: &str| Resource::parse(&Json::from_str(s).unwrap()).unwrap_err();
+
+    assert_eq!(parse(r#"[]"#),
+               HalError::WrongType { path: "".to_string(), expected: "object", found: "array" });
+    assert_eq!(parse(r#"{"_links":"/orders"}"#),
+               HalError::WrongType { path: "/_links".to_string(), expected: "object", found: "string" });
+    assert_eq!(parse(r#"{"_links":{"self":{"title":"Orders"}}}"#),
+               HalError::MissingField { path: "/_links/self".to_string(), field: "href".to_string() });
+    assert_eq!(parse(r#"{"_links":{"self":{"href":"/orders","title":7}}}"#),
+               HalError::WrongType { path: "/_links/self/title".to_string(), expected: "string", found: "number" });
+    assert_eq!(parse(r#"{"_links":{"a/b":{"href":"/orders","templated":"yes"}}}"#).to_string(),
+               "expected boolean at `/_links/a~1b/templated`, found string");
+}
+
+#[test]
+fn link_parse_errors() {
+    let json = Json::from_str(r#"{"href":null}"#).unwrap();
+    assert_eq!(Link::parse(&json),
+               Err(HalError::WrongType { path: "/href".to_string(), expected: "string", found: "null" }));
+}
+
+#[test]
+fn hal_parse_embedded() {
+    let json_str = r#"{"_embedded":{"author":{"_links":{"self":{"href":"/people/1"}},"name":"Fred"},"ea:order":[{"_links":{"self":{"href":"/orders/123"}},"total":30.0},{"_embedded":{"ea:basket":[{"_links":{"self":{"href":"/baskets/98712"}}}]},"_links":{"self":{"href":"/orders/124"}}}]},"_links":{"self":{"href":"/orders"}}}"#;
+    let json = Json::from_str(json_str).unwrap();
+
+    let mut hal = Resource::with_self("/orders");
+    hal.set_embedded("author", Resource::with_self("/people/1").add_state("name", "Fred"))
+        .add_resource("ea:order", Resource::with_self("/orders/123").add_state("total", 30.00_f64))
+        .add_resource("ea:order",
+            Resource::with_self("/orders/124")
+                .add_resource("ea:basket", &Resource::with_self("/baskets/98712"))
+        );
+
+    assert_eq!(Ok(hal.clone()), Resource::parse(&json));
+    assert_eq!(hal.to_json().to_string(), json_str);
+}
+
+#[test]
+fn hal_parse_empty_links() {
+    let json_str = r#"{"_links":{"item":[],"self":{"href":"/orders"}}}"#;
+    let hal = Resource::parse(&Json::from_str(json_str).unwrap()).unwrap();
+
+    assert!(hal.get_links("item").is_empty());
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["item", "self"]);
+    assert_eq!(hal.to_json().to_string(), json_str);
+    assert_eq!(hal.to_string(), json_str);
+
+    let json_str = r#"{"_links":{"item":[]}}"#;
+    assert_eq!(Resource::parse(&Json::from_str(json_str).unwrap()).unwrap().to_string(), json_str);
+}
+
+#[test]
+fn hal_parse_empty_embedded() {
+    let json_str = r#"{"_embedded":{"item":[]},"_links":{"self":{"href":"/orders"}}}"#;
+    let hal = Resource::parse(&Json::from_str(json_str).unwrap()).unwrap();
+
+    assert!(hal.get_embedded("item").is_empty());
+    assert_eq!(hal.embedded_rels().collect::<Vec<_>>(), vec!["item"]);
+    assert_eq!(hal.to_json().to_string(), json_str);
+    assert_eq!(hal.to_string(), json_str);
+}
+
+#[test]
+fn hal_parse_embedded_errors() {
+    let parse = |s: &str| Resource::parse(&Json::from_str(s).unwrap()).unwrap_err();
+
+    assert_eq!(parse(r#"{"_embedded":[]}"#),
+               HalError::WrongType { path: "/_embedded".to_string(), expected: "object", found: "array" });
+    assert_eq!(parse(r#"{"_embedded":{"ea:order":[{},"/orders/124"]}}"#),
+               HalError::WrongType { path: "/_embedded/ea:order/1".to_string(), expected: "object", found: "string" });
+    assert_eq!(parse(r#"{"_embedded":{"author":{"_links":{"self":{}}}}}"#),
+               HalError::MissingField { path: "/_embedded/author/_links/self".to_string(), field: "href".to_string() });
+}
+
+#[test]
+fn hal_parse_link_arrays() {
+    let json_str = r#"{"_links":{"curies":[{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true}],"ea:admin":[{"href":"/admins/2","title":"Fred"},{"href":"/admins/5","title":"Kate"}],"self":{"href":"/orders"}}}"#;
+    let json = Json::from_str(json_str).unwrap();
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:admin", Link::new("/admins/2").title("Fred"))
+        .add_link("ea:admin", Link::new("/admins/5").title("Kate"));
+
+    assert_eq!(Ok(hal), Resource::parse(&json));
+
+    let json = Json::from_str(r#"{"_links":{"ea:admin":[{"href":"/admins/2"},{"title":"Kate"}]}}"#).unwrap();
+    assert_eq!(Resource::parse(&json),
+               Err(HalError::MissingField { path: "/_links/ea:admin/1".to_string(), field: "href".to_string() }));
+}
+
+#[test]
+fn hal_spec_round_trip() {
+    let json_str = r#"{"_embedded":{"ea:order":[{"_links":{"ea:basket":{"href":"/baskets/98712"},"ea:customer":{"href":"/customers/7809"},"self":{"href":"/orders/123"}},"currency":"USD","status":"shipped","total":30.0},{"_links":{"ea:basket":{"href":"/baskets/97213"},"ea:customer":{"href":"/customers/12369"},"self":{"href":"/orders/124"}},"currency":"USD","status":"processing","total":20.0}]},"_links":{"curies":[{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true}],"ea:admin":[{"href":"/admins/2","title":"Fred"},{"href":"/admins/5","title":"Kate"}],"ea:find":{"href":"/orders{?id}","templated":true},"next":{"href":"/orders?page=2"},"self":{"href":"/orders"}},"currentlyProcessing":14,"shippedToday":14}"#;
+    let json = Json::from_str(json_str).unwrap();
+
+    let hal = Resource::parse(&json).unwrap();
+    assert_eq!(hal.to_json().to_string(), json_str);
+}
+
+#[test]
+#[allow(deprecated)]
+fn hal_from_json_malformed() {
+    assert_eq!(Resource::from_json(Json::from_str("[]").unwrap()), Resource::new());
+    assert_eq!(Resource::from_json(Json::from_str(r#"{"_links":{"self":{}}}"#).unwrap()), Resource::new());
+}
+
+#[test]
+fn hal_get_state_as() {
+    let mut hal = Resource::new();
+    hal.add_state("total", 20i64)
+        .add_state("friends", vec!["Mary", "Timmy"])
+        .add_state("errors", ());
+
+    assert_eq!(hal.get_state("total"), Some(&I64(20)));
+    assert_eq!(hal.get_state_as::<u8>("total"), Ok(20));
+    assert_eq!(hal.get_state_as::<f64>("total"), Ok(20.0));
+    assert_eq!(hal.get_state_as::<Vec<String>>("friends"),
+               Ok(vec!["Mary".to_string(), "Timmy".to_string()]));
+    assert_eq!(hal.get_state_as::<Option<bool>>("errors"), Ok(None));
+    assert_eq!(hal.get_state_as::<Option<bool>>("missing"), Ok(None));
+    assert_eq!(hal.get_state_as::<Vec<i64>>("friends"),
+               Err(HalError::WrongType { path: "/friends/0".to_string(), expected: "i64", found: "string" }));
+    assert_eq!(hal.get_state_as::<bool>("missing"),
+               Err(HalError::MissingField { path: "".to_string(), field: "missing".to_string() }));
+}
+
+#[test]
+fn hal_accessors() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_link("ea:admin", Link::new("/admins/2").title("Fred"))
+        .add_link("ea:admin", Link::new("/admins/5").title("Kate"))
+        .add_state("total", 20i64)
+        .add_state("currency", "USD")
+        .add_resource("ea:order", &Resource::with_self("/orders/123"))
+        .add_resource("ea:order", &Resource::with_self("/orders/124"));
+
+    assert_eq!(hal.self_link().map(|link| link.get_href()), Some("/orders"));
+    assert_eq!(hal.get_link("ea:admin").and_then(|link| link.get_title()), Some("Fred"));
+    assert_eq!(hal.get_links("ea:admin").len(), 2);
+    assert_eq!(hal.get_link("next"), None);
+    assert!(hal.get_links("next").is_empty());
+
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["ea:admin", "self"]);
+    assert_eq!(hal.links().map(|(rel, links)| (rel, links.len())).collect::<Vec<_>>(),
+               vec![("ea:admin", 2), ("self", 1)]);
+    assert_eq!(hal.state_entries().map(|(key, _)| key).collect::<Vec<_>>(),
+               vec!["currency", "total"]);
+
+    let order = hal.get_embedded_resource("ea:order").unwrap();
+    assert_eq!(order.self_link().unwrap().get_href(), "/orders/123");
+    assert_eq!(hal.get_embedded("ea:order").len(), 2);
+    assert_eq!(hal.embedded_rels().collect::<Vec<_>>(), vec!["ea:order"]);
+    assert_eq!(hal.embedded().count(), 1);
+    assert!(hal.get_embedded("ea:customer").is_empty());
+}
+
+#[test]
+fn link_accessors() {
+    let mut link = Link::new("/orders{?id}");
+    link.templated(true).media_type("application/hal+json").name("find");
+
+    assert_eq!(link.get_href(), "/orders{?id}");
+    assert_eq!(link.get_templated(), Some(true));
+    assert_eq!(link.get_media_type(), Some("application/hal+json"));
+    assert_eq!(link.get_name(), Some("find"));
+    assert_eq!(link.get_title(), None);
+    assert_eq!(link.get_deprecation(), None);
+}
+
+#[test]
+fn hal_remove_and_replace() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_link("ea:admin", &Link::new("/admins/2"))
+        .add_link("ea:admin", &Link::new("/internal/admins/5"))
+        .add_link("ea:internal", &Link::new("/internal/metrics"))
+        .add_state("total", 20i64)
+        .add_state("cost", 12i64)
+        .add_state("_secret", "x")
+        .add_resource("ea:order", &Resource::with_self("/orders/123"));
+
+    assert_eq!(hal.remove_state("cost"), Some(I64(12)));
+    assert_eq!(hal.remove_state("cost"), None);
+    hal.retain_state(|key, _| !key.starts_with('_'))
+        .remove_links_where(|_, link| link.get_href().starts_with("/internal"));
+
+    let output = r#"{"_embedded":{"ea:order":[{"_links":{"self":{"href":"/orders/123"}}}]},"_links":{"ea:admin":{"href":"/admins/2"},"self":{"href":"/orders"}},"total":20}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+
+    assert_eq!(hal.remove_link("ea:admin"), Some(vec![Link::new("/admins/2")]));
+    hal.set_links("next", vec![Link::new("/orders?page=2")])
+        .set_links("self", vec![])
+        .set_resources("ea:order", vec![Resource::with_self("/orders/124")]);
+
+    let output = r#"{"_embedded":{"ea:order":[{"_links":{"self":{"href":"/orders/124"}}}]},"_links":{"next":{"href":"/orders?page=2"}},"total":20}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+
+    hal.add_resource("ea:order", &Resource::with_self("/orders/125"))
+        .retain_embedded(|_, order| order.self_link().unwrap().get_href() != "/orders/124");
+    assert_eq!(hal.get_embedded("ea:order").len(), 1);
+    hal.retain_embedded(|_, _| false)
+        .retain_links(|rel, _| rel == "self");
+    assert_eq!(hal.remove_embedded("ea:order"), None);
+    assert_eq!(hal.to_json().to_string(), r#"{"total":20}"#);
+}
+
+#[test]
+fn hal_reserved_state_keys() {
+    let mut hal = Resource::new();
+    assert_eq!(hal.try_add_state("_links", "x").unwrap_err(),
+               HalError::ReservedKey("_links".to_string()));
+    assert_eq!(hal.try_add_state("_embedded", ()).unwrap_err().to_string(),
+               "`_embedded` is reserved by Hal and cannot be used as a state key");
+    assert!(hal.try_add_state("_private", true).is_ok());
+    assert_eq!(hal.to_json().to_string(), r#"{"_private":true}"#);
+}
+
+#[test]
+#[should_panic(expected = "`_links` is reserved by Hal")]
+fn hal_add_reserved_state_key() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_state("_links", "/orders/1");
+}
+
+#[test]
+fn hal_links_as_array() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_link("item", &Link::new("/orders/123"))
+        .add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .links_as_array("item", true)
+        .links_as_array("curies", false);
+
+    let output = r#"{"_links":{"curies":{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true},"item":[{"href":"/orders/123"}],"self":{"href":"/orders"}}}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+
+    hal.add_curie("acme", "http://acme.com/rels/{rel}");
+    assert!(hal.to_json().to_string().contains(r#""curies":[{"#));
+}
+
+#[test]
+fn hal_array_rels() {
+    let mut order = Resource::with_self("/orders/123");
+    order.add_link("acme:widget", &Link::new("/widgets/1"));
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_link("acme:widget", &Link::new("/widgets/2"))
+        .add_resource("ea:order", &order)
+        .add_array_rel("acme:widget")
+        .add_resource("ea:order", &order);
+    assert!(hal.is_array_rel("acme:widget"));
+    assert!(hal.get_embedded("ea:order").iter().all(|order| order.is_array_rel("acme:widget")));
+    assert!(!order.is_array_rel("acme:widget"));
+
+    let output = concat!(r#"{"_embedded":{"ea:order":["#,
+                         r#"{"_links":{"acme:widget":[{"href":"/widgets/1"}],"self":{"href":"/orders/123"}}},"#,
+                         r#"{"_links":{"acme:widget":[{"href":"/widgets/1"}],"self":{"href":"/orders/123"}}}]},"#,
+                         r#""_links":{"acme:widget":[{"href":"/widgets/2"}],"self":{"href":"/orders"}}}"#);
+    assert_eq!(hal.to_json().to_string(), output);
+
+    hal.links_as_array("acme:widget", false);
+    assert!(hal.to_json().to_string().ends_with(r#""_links":{"acme:widget":{"href":"/widgets/2"},"self":{"href":"/orders"}}}"#));
+
+    hal.remove_array_rel("acme:widget");
+    assert!(!hal.get_embedded("ea:order")[0].is_array_rel("acme:widget"));
+
+    // The shape of single links does not count towards equality
+    let mut item = Resource::new();
+    item.add_link("item", &Link::new("/items/1"));
+    assert_eq!(Resource::parse(&Json::from_str(r#"{"_links":{"item":[{"href":"/items/1"}]}}"#).unwrap()), Ok(item));
+}
+
+#[test]
+fn hal_link_shape_round_trip() {
+    let input = r#"{"_links":{"curies":{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true},"item":[{"href":"/orders/123"}],"self":{"href":"/orders"}}}"#;
+
+    let hal = Resource::parse(&Json::from_str(input).unwrap()).unwrap();
+    assert_eq!(hal.to_json().to_string(), input);
+}
+
+#[test]
+fn hal_set_embedded() {
+    let mut hal = Resource::with_self("/orders/123");
+    hal.set_embedded("author", &Resource::with_self("/people/1"))
+        .add_resource("item", &Resource::with_self("/items/1"));
+
+    let output = r#"{"_embedded":{"author":{"_links":{"self":{"href":"/people/1"}}},"item":[{"_links":{"self":{"href":"/items/1"}}}]},"_links":{"self":{"href":"/orders/123"}}}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+    assert_eq!(Resource::parse(&Json::from_str(output).unwrap()), Ok(hal.clone()));
+
+    hal.set_embedded("author", &Resource::with_self("/people/2"));
+    assert_eq!(hal.get_embedded("author").len(), 1);
+    assert_eq!(hal.get_embedded_resource("author").and_then(|author| author.self_link()).map(|link| link.get_href()),
+               Some("/people/2"));
+
+    hal.add_resource("author", &Resource::with_self("/people/3"));
+    assert!(hal.to_json().to_string().starts_with(r#"{"_embedded":{"author":[{"#));
+}
+
+#[test]
+fn hal_encode_matches_to_json() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("ea:find", Link::new("/orders{?id}").templated(true))
+        .add_state("Total", 14i64)
+        .add_state("tags", vec!["new", "paid"])
+        .add_state("price", 20.5_f64)
+        .set_embedded("author", &Resource::with_self("/people/1"))
+        .add_resource("ea:order", Resource::with_self("/orders/123").add_state("total", 30.00_f64));
+
+    assert_eq!(json::encode(&hal).unwrap(), hal.to_json().to_string());
+    assert_eq!(json::as_pretty_json(&hal).to_string(), hal.to_json().pretty().to_string());
+}
+
+#[test]
+fn hal_preserve_order() {
+    let mut hal = Resource::new();
+    hal.preserve_order(true)
+        .add_state("id", 123i64)
+        .add_link("self", &Link::new("/orders/123"))
+        .add_link("next", &Link::new("/orders/124"))
+        .add_link("ea:basket", &Link::new("/baskets/98712"))
+        .add_state("name", "Fred")
+        .add_resource("items", &Resource::with_self("/items/1"))
+        .add_state("currency", "USD");
+
+    let output = r#"{"id":123,"_links":{"self":{"href":"/orders/123"},"next":{"href":"/orders/124"},"ea:basket":{"href":"/baskets/98712"}},"name":"Fred","_embedded":{"items":[{"_links":{"self":{"href":"/items/1"}}}]},"currency":"USD"}"#;
+    assert_eq!(json::encode(&hal).unwrap(), output);
+    assert_eq!(hal.to_string(), output);
+    assert_eq!(format!("{:#}", hal), json::as_pretty_json(&hal).to_string());
+    assert_eq!(hal.state_entries().map(|(key, _)| key).collect::<Vec<_>>(), vec!["id", "name", "currency"]);
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["self", "next", "ea:basket"]);
+
+    hal.remove_state("id");
+    hal.remove_link("self");
+    hal.add_state("id", 123i64)
+        .add_link("self", &Link::new("/orders/123"));
+    assert_eq!(hal.state_entries().map(|(key, _)| key).collect::<Vec<_>>(), vec!["name", "currency", "id"]);
+    assert_eq!(hal.link_rels().collect::<Vec<_>>(), vec!["next", "ea:basket", "self"]);
+}
+
+#[test]
+fn hal_parse_ordered() {
+    let input = r#"{"_links":{"self":{"href":"/orders"},"next":{"href":"/orders?page=2"}},"shippedToday":20,"currentlyProcessing":14,"_embedded":{"ea:order":[{"_links":{"self":{"href":"/orders/123"}},"total":30.0,"currency":"USD"}],"author":{"name":"Fred","_links":{"self":{"href":"/people/1"}}}}}"#;
+
+    let hal = Resource::parse_with(input, ParseOptions::new().preserve_order(true)).unwrap();
+    assert_eq!(json::encode(&hal).unwrap(), input);
+    assert_eq!(hal.embedded_rels().collect::<Vec<_>>(), vec!["ea:order", "author"]);
+    assert_eq!(Resource::parse(&Json::from_str(input).unwrap()).unwrap().to_json(), hal.to_json());
+
+    assert_eq!(Resource::parse_with(r#"{"_links":{"self":{"href":"/orders"}},}"#, ParseOptions::new().preserve_order(true)),
+               Err(HalError::Syntax { line: 1, column: 39, reason: "trailing comma" }));
+    assert_eq!(Resource::parse_with(r#"{"_links":{"self":{}}}"#, ParseOptions::new().preserve_order(true)),
+               Err(HalError::MissingField { path: "/_links/self".to_string(), field: "href".to_string() }));
+}
+
+#[test]
+fn hal_add_by_value() {
+    let mut order = Resource::with_self("/orders/123");
+    order.add_state("status", "shipped".to_string());
+
+    let mut basket = Link::new("/baskets/98712");
+    basket.title("Basket");
+
+    let mut by_value = Resource::with_self("/orders");
+    by_value.add_link_owned("next", Link::new("/orders?page=2"))
+        .add_link_owned("ea:basket", basket.clone())
+        .add_resource_owned("ea:order", order.clone())
+        .set_embedded_owned("ea:customer", Resource::with_self("/customers/7809"));
+
+    let mut by_reference = Resource::with_self("/orders");
+    by_reference.add_link("next", &Link::new("/orders?page=2"))
+        .add_link("ea:basket", &basket)
+        .add_resource("ea:order", &order)
+        .set_embedded("ea:customer", &Resource::with_self("/customers/7809"));
+
+    assert_eq!(by_value, by_reference);
+}
+
+#[test]
+fn hal_spec_by_value() {
+    let mut first = Resource::with_self("/orders/123");
+    first.add_link_owned("ea:basket", Link::new("/baskets/98712"))
+        .add_link_owned("ea:customer", Link::new("/customers/7809"))
+        .add_state("total", 30.0f64)
+        .add_state("currency", "USD".to_string())
+        .add_state("status", "shipped".to_string());
+
+    let mut second = Resource::with_self("/orders/124");
+    second.add_link_owned("ea:basket", Link::new("/baskets/97213"))
+        .add_link_owned("ea:customer", Link::new("/customers/12369"))
+        .add_state("total", 20.0f64)
+        .add_state("currency", "USD".to_string())
+        .add_state("status", "processing".to_string());
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link_owned("next", Link::new("/orders?page=2"))
+        .add_link("ea:find", Link::new("/orders{?id}").templated(true))
+        .add_link("ea:admin", Link::new("/admins/2").title("Fred"))
+        .add_link("ea:admin", Link::new("/admins/5").title("Kate"))
+        .add_state("currentlyProcessing", 14i64)
+        .add_state("shippedToday", 14i64)
+        .add_resource_owned("ea:order", first)
+        .add_resource_owned("ea:order", second);
+
+    let output = r#"{"_embedded":{"ea:order":[{"_links":{"ea:basket":{"href":"/baskets/98712"},"ea:customer":{"href":"/customers/7809"},"self":{"href":"/orders/123"}},"currency":"USD","status":"shipped","total":30.0},{"_links":{"ea:basket":{"href":"/baskets/97213"},"ea:customer":{"href":"/customers/12369"},"self":{"href":"/orders/124"}},"currency":"USD","status":"processing","total":20.0}]},"_links":{"curies":[{"href":"http://example.com/docs/rels/{rel}","name":"ea","templated":true}],"ea:admin":[{"href":"/admins/2","title":"Fred"},{"href":"/admins/5","title":"Kate"}],"ea:find":{"href":"/orders{?id}","templated":true},"next":{"href":"/orders?page=2"},"self":{"href":"/orders"}},"currentlyProcessing":14,"shippedToday":14}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+}
+
+#[test]
+fn into_hal_state() {
+    assert_eq!("Mary".to_string().into_hal_state(), "Mary".to_hal_state());
+    assert_eq!(vec!["Mary".to_string(), "Timmy".to_string()].into_hal_state(),
+               vec!["Mary", "Timmy"].to_hal_state());
+    assert_eq!(Some("Mary".to_string()).into_hal_state(), "Mary".to_hal_state());
+
+    let json = Json::from_str(r#"{"friends":["Mary","Timmy"],"total":20.0}"#).unwrap();
+    assert_eq!(json.clone().into_hal_state(), json.to_hal_state());
+    assert_eq!(json.to_hal_state().into_hal_state(), json.to_hal_state());
+}
@@ -0,0 +1,81 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::curie::CurieIssueKind;
+use hal::lint::{self, Rule, Severity};
+use hal::link::Link;
+use hal::resource::Resource;
+use serialize::json::Json;
+
+fn rules(diagnostics: &[lint::Diagnostic]) -> Vec<(&str, Rule)> {
+    diagnostics.iter().map(|d| (&d.path[..], d.rule.clone())).collect()
+}
+
+#[test]
+fn clean_resource() {
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("next", &Link::new("/orders?page=2"))
+        .add_link("ea:find", &Link::new("/orders{?id}"))
+        .add_resource("ea:order", &Resource::with_self("/orders/123"));
+
+    assert_eq!(lint::check(&hal), vec![]);
+}
+
+#[test]
+fn check_resource() {
+    let mut order = Resource::new();
+    order.add_state("_secret", "hunter2")
+        .add_link("nxt", &Link::new("/orders/124"));
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_link("next", Link::new("/orders?page=2").templated(true))
+        .add_link("search", Link::new("").deprecation("soon"))
+        .add_link("ea:find", Link::new("/orders{?id}").templated(false))
+        .add_link("item", Link::new("/orders/1").name("one"))
+        .add_link("item", Link::new("/orders/2").name("one"))
+        .add_resource("item", &order);
+
+    let diagnostics = lint::check(&hal);
+    assert_eq!(rules(&diagnostics), vec![
+        ("/_embedded/item/0", Rule::MissingSelf),
+        ("/_embedded/item/0/_links/nxt", Rule::UnregisteredRel),
+        ("/_embedded/item/0/_secret", Rule::UnderscoreStateKey),
+        ("/_links/ea:find", Rule::Curie(CurieIssueKind::UndeclaredPrefix("ea".to_string()))),
+        ("/_links/ea:find/href", Rule::NotMarkedTemplated),
+        ("/_links/item/1/name", Rule::DuplicateLinkName),
+        ("/_links/next/templated", Rule::TemplatedWithoutExpressions),
+        ("/_links/search/deprecation", Rule::DeprecationNotUrl),
+        ("/_links/search/href", Rule::EmptyHref),
+    ]);
+
+    assert_eq!(diagnostics[1].severity, Severity::Info);
+    assert_eq!(diagnostics[8].severity, Severity::Error);
+    assert_eq!(diagnostics[0].to_string(), "warning: resource has no self link at `/_embedded/item/0`");
+}
+
+#[test]
+fn check_json() {
+    let json = Json::from_str(r#"{
+        "_links": {
+            "self": { "href": "/orders" },
+            "curies": { "name": "ea", "href": "http://example.com/docs/rels/{rel}", "templated": true },
+            "ea:find": { "href": "/orders{?id", "templated": true }
+        }
+    }"#).unwrap();
+
+    let diagnostics = lint::check_json(&json);
+    assert_eq!(rules(&diagnostics), vec![
+        ("/_links/curies", Rule::CuriesNotArray),
+        ("/_links/ea:find/href", Rule::InvalidTemplate),
+    ]);
+}
+
+#[test]
+fn check_malformed_json() {
+    let json = Json::from_str(r#"{ "_links": { "self": { "title": "Orders" } } }"#).unwrap();
+
+    let diagnostics = lint::check_json(&json);
+    assert_eq!(rules(&diagnostics), vec![("/_links/self", Rule::Malformed)]);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+}
@@ -0,0 +1,58 @@
+extern crate hal;
+
+use hal::link::Link;
+use hal::rel::{self, Rel, RelKind, REGISTERED};
+use hal::resource::Resource;
+
+#[test]
+fn registered_rels_are_sorted() {
+    assert!(REGISTERED.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn rel_kind() {
+    assert_eq!(Rel::NEXT.kind(), RelKind::Registered);
+    assert_eq!(Rel::from("item").kind(), RelKind::Registered);
+    assert_eq!(Rel::from("ea:order").kind(), RelKind::Curie);
+    assert_eq!(Rel::new("http://example.com/rels/order").kind(), RelKind::Extension);
+    assert_eq!(Rel::from("nxt").kind(), RelKind::Unregistered);
+    assert_eq!(rel::kind("urn:isbn:123"), RelKind::Curie);
+    assert_eq!(rel::kind("://example.com"), RelKind::Unregistered);
+
+    assert!(Rel::SELF.is_registered());
+    assert!(Rel::from("ea:order").is_curie());
+    assert!(Rel::from("https://example.com/rels/order").is_uri());
+}
+
+#[test]
+fn rel_as_rel_argument() {
+    let mut hal = Resource::new();
+    hal.add_link(Rel::SELF, &Link::new("/orders"))
+        .add_link(Rel::NEXT, &Link::new("/orders?page=2"))
+        .add_link(Rel::from("ea:admin"), &Link::new("/admins/2"))
+        .add_resource(Rel::ITEM, &Resource::with_self("/orders/123"));
+
+    assert_eq!(hal.self_link().map(|link| link.get_href()), Some("/orders"));
+    assert_eq!(hal.get_link(&Rel::NEXT).map(|link| link.get_href()), Some("/orders?page=2"));
+    assert!(hal.get_link("ea:admin").is_some());
+    assert_eq!(hal.get_embedded(&Rel::ITEM).len(), 1);
+    assert_eq!(String::from(Rel::COLLECTION), "collection");
+    assert_eq!(Rel::EDIT_FORM.to_string(), "edit-form");
+}
+
+#[test]
+fn unregistered_rels() {
+    let mut order = Resource::with_self("/orders/123");
+    order.add_link("bsket", &Link::new("/baskets/98712"));
+
+    let mut hal = Resource::with_self("/orders");
+    hal.add_curie("ea", "http://example.com/docs/rels/{rel}")
+        .add_link("nxt", &Link::new("/orders?page=2"))
+        .add_link("ea:admin", &Link::new("/admins/2"))
+        .add_link("http://example.com/rels/other", &Link::new("/other"))
+        .add_resource("orders", &order)
+        .add_resource("item", &Resource::with_self("/orders/124"));
+
+    assert_eq!(hal.unregistered_rels(),
+               vec!["/_links/nxt", "/_embedded/orders", "/_embedded/orders/0/_links/bsket"]);
+}
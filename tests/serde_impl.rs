@@ -0,0 +1,195 @@
+#![cfg(feature = "serde")]
+
+extern crate hal;
+extern crate rustc_serialize as serialize;
+extern crate serde_json;
+#[macro_use]
+extern crate serde_derive;
+
+use hal::error::HalError;
+use hal::link::Link;
+use hal::resource::{ParseOptions, Resource};
+use hal::state::HalState;
+use serialize::json::{Json, ToJson};
+use std::collections::BTreeMap;
+
+mod common;
+
+use common::spec;
+
+#[test]
+fn serialize_resource() {
+    let hal = spec();
+    assert_eq!(serde_json::to_string(&hal).unwrap(), hal.to_json().to_string());
+}
+
+#[test]
+fn serialize_shapes() {
+    let mut hal = Resource::with_self("/orders/123");
+    hal.add_link("item", &Link::new("/items/1"))
+        .links_as_array("item", true)
+        .set_embedded("author", &Resource::with_self("/people/1"));
+
+    let json = serde_json::to_string(&hal).unwrap();
+    assert_eq!(json, hal.to_json().to_string());
+
+    let parsed: Resource = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+}
+
+#[test]
+fn serialize_link() {
+    let mut link = Link::new("https://www.example.com");
+    link.templated(true)
+        .deprecation("https://www.example.com/newer")
+        .media_type("text/html")
+        .name("example")
+        .title("An example link")
+        .profile("http://tools.ietf.org/html/draft-wilde-profile-link-04")
+        .hreflang("en");
+
+    assert_eq!(serde_json::to_string(&link).unwrap(), link.to_json().to_string());
+}
+
+#[test]
+fn deserialize_resource() {
+    let hal = spec();
+    let json = hal.to_json().to_string();
+
+    let parsed: Resource = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, Resource::parse(&Json::from_str(&json).unwrap()).unwrap());
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+}
+
+#[test]
+fn deserialize_state() {
+    let state: HalState = serde_json::from_str(r#"{"a":[1,-2,2.5,"x",true,null]}"#).unwrap();
+    let expected = r#"{"a":[1,-2,2.5,"x",true,null]}"#;
+    assert_eq!(serde_json::to_string(&state).unwrap(), expected);
+}
+
+#[test]
+fn deserialize_invalid_link() {
+    let err = serde_json::from_str::<Resource>(r#"{"_links":{"self":{"title":"x"}}}"#).unwrap_err();
+    assert_eq!(err.to_string(), "missing field `href` at `/_links/self`");
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    total: f64,
+    currency: &'static str,
+    status: Status,
+    shipped_to: Option<Address>,
+    tags: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct Address {
+    city: &'static str,
+}
+
+#[derive(Serialize)]
+#[allow(dead_code)]
+enum Status {
+    Processing,
+    Shipped { carrier: &'static str },
+}
+
+#[test]
+fn resource_from_serializable() {
+    let order = Order {
+        total: 20.00,
+        currency: "USD",
+        status: Status::Shipped { carrier: "UPS" },
+        shipped_to: Some(Address { city: "Portland" }),
+        tags: vec!["gift"],
+    };
+
+    let hal = Resource::from_serializable(&order).unwrap();
+    let output = r#"{"currency":"USD","shippedTo":{"city":"Portland"},"status":{"Shipped":{"carrier":"UPS"}},"tags":["gift"],"total":20.0}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+
+    let mut hal = Resource::with_self("/orders/1");
+    hal.add_state_from(&order).unwrap().add_state("total", 25.00_f64);
+    let output = r#"{"_links":{"self":{"href":"/orders/1"}},"currency":"USD","shippedTo":{"city":"Portland"},"status":{"Shipped":{"carrier":"UPS"}},"tags":["gift"],"total":25.0}"#;
+    assert_eq!(hal.to_json().to_string(), output);
+}
+
+#[test]
+fn add_state_from_preserving_order() {
+    let order = Order {
+        total: 20.00,
+        currency: "USD",
+        status: Status::Processing,
+        shipped_to: None,
+        tags: vec![],
+    };
+
+    let mut hal = Resource::with_self("/orders/1");
+    hal.preserve_order(true).add_state_from(&order).unwrap();
+    let output = r#"{"_links":{"self":{"href":"/orders/1"}},"total":20.0,"currency":"USD","status":"Processing","shippedTo":null,"tags":[]}"#;
+    assert_eq!(hal.to_string(), output);
+    assert_eq!(serde_json::to_string(&hal).unwrap(), output);
+}
+
+#[test]
+fn add_state_from_non_object() {
+    let mut hal = Resource::new();
+    assert_eq!(hal.add_state_from(&vec![1, 2]).unwrap_err(),
+               HalError::WrongType { path: "".to_string(), expected: "object", found: "array" });
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], "x");
+    assert_eq!(hal.add_state_from(&map).unwrap_err(),
+               HalError::Serialize("map keys must be strings".to_string()));
+}
+
+#[test]
+fn add_state_from_reserved_key() {
+    let mut map = BTreeMap::new();
+    map.insert("_embedded", "x");
+    assert_eq!(Resource::from_serializable(&map).unwrap_err(),
+               HalError::ReservedKey("_embedded".to_string()));
+}
+
+#[test]
+fn serialize_preserving_order() {
+    let input = r#"{"_links":{"self":{"href":"/orders/123"},"ea:basket":{"href":"/baskets/98712"}},"total":30.0,"currency":"USD","_embedded":{"ea:customer":{"_links":{"self":{"href":"/customers/7809"}}}}}"#;
+    let hal = Resource::parse_with(input, ParseOptions::new().preserve_order(true)).unwrap();
+    assert_eq!(serde_json::to_string(&hal).unwrap(), input);
+}
+
+#[test]
+fn serialize_lazy_resources() {
+    let lazy = || {
+        let mut hal = Resource::with_self("/orders");
+        hal.add_resource("ea:order", &Resource::with_self("/orders/1"));
+        let mut hal = hal.into_streaming();
+        hal.add_lazy_resources("ea:order", (2..4).map(|id| Resource::with_self(format!("/orders/{}", id))));
+        hal
+    };
+
+    let mut out = Vec::new();
+    lazy().serialize(&mut serde_json::Serializer::new(&mut out)).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), lazy().into_json().to_string());
+}
+
+#[test]
+fn serialize_templates() {
+    use hal::forms::{Property, Template};
+
+    let mut hal = Resource::with_self("/orders/123");
+    hal.add_template("default", Template::new("PUT")
+        .property(Property::new("total").required(true).min(0.0).max_length(8)));
+
+    let json = serde_json::to_string(&hal).unwrap();
+    assert_eq!(json, hal.to_json().to_string());
+
+    let parsed: Resource = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, hal);
+    assert_eq!(parsed.get_template("default").map(|template| template.get_method()), Some("PUT"));
+
+    let malformed = r#"{"_templates":{"default":"not a template"}}"#;
+    assert!(serde_json::from_str::<Resource>(malformed).is_err());
+}
@@ -0,0 +1,203 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::error::HalError;
+use hal::link::Link;
+use hal::state::{HalObject, HalState, ToHalState};
+use hal::uri_template::UriTemplate;
+use serialize::json::{Json, ToJson};
+use std::collections::BTreeMap;
+
+/// The variables from section 3.2 of RFC 6570
+fn vars() -> HalObject {
+    let mut keys = BTreeMap::new();
+    keys.insert("semi".to_string(), ";");
+    keys.insert("dot".to_string(), ".");
+    keys.insert("comma".to_string(), ",");
+
+    let mut vars = BTreeMap::new();
+    vars.insert("count".to_string(), vec!["one", "two", "three"].to_hal_state());
+    vars.insert("dom".to_string(), vec!["example", "com"].to_hal_state());
+    vars.insert("dub".to_string(), "me/too".to_hal_state());
+    vars.insert("hello".to_string(), "Hello World!".to_hal_state());
+    vars.insert("half".to_string(), "50%".to_hal_state());
+    vars.insert("var".to_string(), "value".to_hal_state());
+    vars.insert("who".to_string(), "fred".to_hal_state());
+    vars.insert("base".to_string(), "http://example.com/home/".to_hal_state());
+    vars.insert("path".to_string(), "/foo/bar".to_hal_state());
+    vars.insert("list".to_string(), vec!["red", "green", "blue"].to_hal_state());
+    vars.insert("keys".to_string(), keys.to_hal_state());
+    vars.insert("v".to_string(), 6i64.to_hal_state());
+    vars.insert("x".to_string(), 1024i64.to_hal_state());
+    vars.insert("y".to_string(), 768i64.to_hal_state());
+    vars.insert("empty".to_string(), "".to_hal_state());
+    vars.insert("empty_keys".to_string(), HalState::Object(BTreeMap::new()));
+    vars.insert("undef".to_string(), HalState::Null);
+    vars
+}
+
+fn assert_expansions(cases: &[(&str, &str)]) {
+    let vars = vars();
+    for &(template, expected) in cases {
+        let uri = UriTemplate::parse(template).unwrap().expand(&vars);
+        assert_eq!(uri, expected, "expanding {}", template);
+    }
+}
+
+#[test]
+fn level_1() {
+    assert_expansions(&[("{var}", "value"),
+                        ("{hello}", "Hello%20World%21"),
+                        ("{half}", "50%25"),
+                        ("O{empty}X", "OX"),
+                        ("O{undef}X", "OX"),
+                        ("{x,y}", "1024,768"),
+                        ("?{x,empty}", "?1024,"),
+                        ("?{x,undef}", "?1024"),
+                        ("{var:3}", "val"),
+                        ("{var:30}", "value")]);
+}
+
+#[test]
+fn level_2() {
+    assert_expansions(&[("{+var}", "value"),
+                        ("{+hello}", "Hello%20World!"),
+                        ("{+half}", "50%25"),
+                        ("{base}index", "http%3A%2F%2Fexample.com%2Fhome%2Findex"),
+                        ("{+base}index", "http://example.com/home/index"),
+                        ("O{+empty}X", "OX"),
+                        ("{+path}/here", "/foo/bar/here"),
+                        ("here?ref={+path}", "here?ref=/foo/bar"),
+                        ("up{+path}{var}/here", "up/foo/barvalue/here"),
+                        ("{+path:6}/here", "/foo/b/here"),
+                        ("{#var}", "#value"),
+                        ("{#hello}", "#Hello%20World!"),
+                        ("{#half}", "#50%25"),
+                        ("foo{#empty}", "foo#"),
+                        ("foo{#undef}", "foo"),
+                        ("X{#path,x}/here", "X#/foo/bar,1024/here")]);
+}
+
+#[test]
+fn level_3() {
+    assert_expansions(&[("map?{x,y}", "map?1024,768"),
+                        ("{x,hello,y}", "1024,Hello%20World%21,768"),
+                        ("{+x,hello,y}", "1024,Hello%20World!,768"),
+                        ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+                        ("X{.var}", "X.value"),
+                        ("X{.x,y}", "X.1024.768"),
+                        ("X{.empty}", "X."),
+                        ("{/var}", "/value"),
+                        ("{/var,x}/here", "/value/1024/here"),
+                        ("{/var,empty}", "/value/"),
+                        ("{;x,y}", ";x=1024;y=768"),
+                        ("{;x,y,empty}", ";x=1024;y=768;empty"),
+                        ("{?x,y}", "?x=1024&y=768"),
+                        ("{?x,y,empty}", "?x=1024&y=768&empty="),
+                        ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+                        ("{&x,y,empty}", "&x=1024&y=768&empty=")]);
+}
+
+#[test]
+fn level_4() {
+    assert_expansions(&[("{var:3}", "val"),
+                        ("{list}", "red,green,blue"),
+                        ("{list*}", "red,green,blue"),
+                        ("{keys}", "comma,%2C,dot,.,semi,%3B"),
+                        ("{keys*}", "comma=%2C,dot=.,semi=%3B"),
+                        ("{+path:6}/here", "/foo/b/here"),
+                        ("{+list}", "red,green,blue"),
+                        ("{+keys*}", "comma=,,dot=.,semi=;"),
+                        ("{#list*}", "#red,green,blue"),
+                        ("{#keys}", "#comma,,,dot,.,semi,;"),
+                        ("X{.list}", "X.red,green,blue"),
+                        ("X{.list*}", "X.red.green.blue"),
+                        ("X{.keys*}", "X.comma=%2C.dot=..semi=%3B"),
+                        ("X{.empty_keys}", "X"),
+                        ("{/var:1,var}", "/v/value"),
+                        ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+                        ("{/keys*}", "/comma=%2C/dot=./semi=%3B"),
+                        ("{;hello:5}", ";hello=Hello"),
+                        ("{;list}", ";list=red,green,blue"),
+                        ("{;list*}", ";list=red;list=green;list=blue"),
+                        ("{;keys*}", ";comma=%2C;dot=.;semi=%3B"),
+                        ("{?var:3}", "?var=val"),
+                        ("{?list}", "?list=red,green,blue"),
+                        ("{?list*}", "?list=red&list=green&list=blue"),
+                        ("{?keys}", "?keys=comma,%2C,dot,.,semi,%3B"),
+                        ("{?keys*}", "?comma=%2C&dot=.&semi=%3B"),
+                        ("{&var:3}", "&var=val"),
+                        ("{&list*}", "&list=red&list=green&list=blue"),
+                        ("{&keys*}", "&comma=%2C&dot=.&semi=%3B"),
+                        ("{count}", "one,two,three"),
+                        ("{/count*}", "/one/two/three"),
+                        ("{dub}", "me%2Ftoo"),
+                        ("{.dom*}", ".example.com")]);
+}
+
+#[test]
+fn malformed_templates() {
+    let error = |template: &str| UriTemplate::parse(template).unwrap_err();
+
+    assert_eq!(error("/orders{?id"),
+               HalError::InvalidTemplate { template: "/orders{?id".to_string(), position: 7, reason: "unclosed `{`" });
+    assert_eq!(error("/orders}").to_string(),
+               "invalid URI Template `/orders}` at offset 7: unmatched `}`");
+    assert_eq!(error("{}").to_string(),
+               "invalid URI Template `{}` at offset 1: empty expression");
+    assert_eq!(error("{a{b}}").to_string(),
+               "invalid URI Template `{a{b}}` at offset 2: nested `{`");
+    assert_eq!(error("{|a}").to_string(),
+               "invalid URI Template `{|a}` at offset 1: operator is reserved for future extensions");
+    assert_eq!(error("{?a,b c}").to_string(),
+               "invalid URI Template `{?a,b c}` at offset 4: invalid character in variable name");
+    assert_eq!(error("{a:0}").to_string(),
+               "invalid URI Template `{a:0}` at offset 3: prefix length must be between 1 and 9999");
+    assert_eq!(error("{?a,}").to_string(),
+               "invalid URI Template `{?a,}` at offset 4: missing variable name");
+}
+
+#[test]
+fn template_variables() {
+    let template = UriTemplate::parse("/orders{/id}{?page,size,id}").unwrap();
+    assert!(template.has_expressions());
+    assert_eq!(template.variables(), vec!["id", "page", "size"]);
+    assert!(!UriTemplate::parse("/orders").unwrap().has_expressions());
+}
+
+#[test]
+fn link_expand() {
+    let mut vars = BTreeMap::new();
+    vars.insert("id".to_string(), "a b".to_hal_state());
+
+    let mut link = Link::new("/orders{?id}");
+    link.templated(true).title("Find");
+
+    let mut expanded = Link::new("/orders?id=a%20b");
+    expanded.title("Find");
+    assert_eq!(link.expand(&vars), Ok(expanded));
+    assert_eq!(Link::new("/admins/{rel}").expand_href(&BTreeMap::new()),
+               Ok("/admins/".to_string()));
+    assert!(Link::new("/orders{?id").expand_href(&vars).is_err());
+}
+
+#[test]
+fn link_detects_templates() {
+    assert_eq!(Link::new("/orders{?id}").get_templated(), Some(true));
+    assert_eq!(Link::new("/orders?id=1").get_templated(), None);
+    assert_eq!(Link::new("/orders{?id").get_templated(), None);
+    assert_eq!(Link::new("/orders{?id}").templated(false).get_templated(), Some(false));
+
+    assert_eq!(Link::try_new("/orders{/id}").map(|link| link.get_templated()), Ok(Some(true)));
+    assert_eq!(Link::try_new("/orders").map(|link| link.get_templated()), Ok(None));
+    assert_eq!(Link::try_new("/orders/{id").unwrap_err(),
+               HalError::InvalidTemplate { template: "/orders/{id".to_string(), position: 8, reason: "unclosed `{`" });
+    assert!(Link::try_new("/orders/id}").is_err());
+}
+
+#[test]
+fn parsed_links_keep_templated_member() {
+    let json = Json::from_str(r#"{"href":"/orders{?id}"}"#).unwrap();
+    assert_eq!(Link::parse(&json).unwrap().get_templated(), None);
+    assert_eq!(Link::parse(&json).unwrap().to_json(), json);
+}
@@ -0,0 +1,138 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use std::convert::TryFrom;
+use hal::error::HalError;
+use hal::link::Link;
+use hal::resource::{ParseOptions, Resource};
+use hal::state::HalState;
+use hal::view::ResourceRef;
+use serialize::json::{Json, ToJson};
+
+mod common;
+
+use common::spec;
+
+#[test]
+fn view_accessors() {
+    let hal = spec();
+    let text = hal.to_json().pretty().to_string();
+    let view = ResourceRef::parse(&text).unwrap();
+
+    assert_eq!(view.self_link().map(Link::from), hal.self_link().cloned());
+    assert_eq!(view.get_links("ea:admin").iter().map(Link::from).collect::<Vec<_>>(),
+               hal.get_links("ea:admin").to_vec());
+    assert!(view.get_link("ea:missing").is_none());
+    assert!(view.link_rels().eq(hal.link_rels()));
+
+    assert_eq!(view.get_state("shippedToday"), Some(&HalState::U64(20)));
+    // State is read once and then borrowed
+    assert!(std::ptr::eq(view.get_state("shippedToday").unwrap(), view.get_state("shippedToday").unwrap()));
+    assert_eq!(view.get_state_as::<i64>("currentlyProcessing"), Ok(14));
+    assert_eq!(view.get_state_as::<Option<String>>("missing"), Ok(None));
+    assert_eq!(view.get_raw_state("shippedToday"), Some("20"));
+    assert_eq!(view.get_raw_state("_links"), None);
+    assert_eq!(view.state_entries().map(|(key, _)| key).collect::<Vec<_>>(),
+               vec!["currentlyProcessing", "shippedToday"]);
+
+    assert!(view.embedded_rels().eq(hal.embedded_rels()));
+    let orders = view.get_embedded("ea:order");
+    assert_eq!(orders.len(), 2);
+    assert_eq!(orders[0].get_link("ea:basket").unwrap().get_name(), Some("basket"));
+    assert_eq!(orders[0].get_state_as::<String>("currency"), Ok("USD".to_string()));
+    let customer = view.get_embedded_resource("ea:customer").unwrap();
+    assert_eq!(customer.self_link().unwrap().get_href(), "/customers/7809");
+
+    assert_eq!(view.expand_rel("ea:order"), Some("http://example.com/docs/rels/order".to_string()));
+    let parsed = Resource::parse(&Json::from_str(&text).unwrap()).unwrap();
+    assert_eq!(view.to_resource(), Ok(parsed.clone()));
+    assert_eq!(Resource::try_from(orders[0].clone()), Ok(parsed.get_embedded("ea:order")[0].clone()));
+}
+
+#[test]
+fn view_borrows_strings() {
+    let text = r#"{"_links":{"self":{"href":"/orders"},"next":{"href":"\/orders?page=2","title":"café 😀"}}}"#;
+    let view = ResourceRef::parse(text).unwrap();
+    assert_eq!(view.as_str(), text);
+
+    let next = view.get_link("next").unwrap();
+    assert_eq!(next.get_href(), "/orders?page=2");
+    assert_eq!(next.get_title(), Some("café 😀"));
+
+    // Only strings with escapes are copied out of the text
+    let within = |s: &str| text.as_ptr() <= s.as_ptr() && s.as_ptr() < text[text.len()..].as_ptr();
+    assert!(within(next.get_title().unwrap()));
+    assert!(!within(next.get_href()));
+
+    let json = Json::from_str(text).unwrap();
+    assert_eq!(view.to_resource(), Resource::parse(&json));
+}
+
+#[test]
+fn view_malformed() {
+    // Syntax errors are reported as `Resource::parse_with` reports them
+    let malformed = [
+        (r#"{"_links":{"self":{"href":"/orders"}},}"#, 1, 39, "trailing comma"),
+        ("{\"total\":\n  14 15}", 2, 7, "invalid syntax"),
+        (r#"{"total":14} x"#, 1, 14, "trailing characters"),
+        (r#"{"total":"#, 1, 10, "EOF While parsing value"),
+    ];
+    for &(text, line, column, reason) in &malformed {
+        assert_eq!(ResourceRef::parse(text), Err(HalError::Syntax { line, column, reason }));
+        assert_eq!(ResourceRef::parse(text), Err(Resource::parse_with(text, ParseOptions::new().preserve_order(true)).unwrap_err()));
+    }
+    // The json parser loses its place after a trailing comma in a nested object
+    assert_eq!(ResourceRef::parse(r#"{"total":{"count":{},}"#),
+               Err(HalError::Syntax { line: 1, column: 22, reason: "invalid syntax" }));
+    assert_eq!(ResourceRef::parse("[]"),
+               Err(HalError::WrongType { path: "".to_string(), expected: "object", found: "array" }));
+
+    // Links and embedded resources of the wrong shape are reported where they are
+    let text = r#"{"_links":{"self":{"title":"no href"},"next":{"href":"/next"}}}"#;
+    assert_eq!(ResourceRef::parse(text),
+               Err(HalError::MissingField { path: "/_links/self".to_string(), field: "href".to_string() }));
+    let text = r#"{"_embedded":{"ea:order":[{"_links":{"self":{"href":"/orders/1"}}},{"_links":{"self":{"href":1}}}]}}"#;
+    assert_eq!(ResourceRef::parse(text),
+               Err(HalError::WrongType {
+                   path: "/_embedded/ea:order/1/_links/self/href".to_string(),
+                   expected: "string",
+                   found: "number",
+               }));
+    let text = r#"{"_links":{"self":{"href":"/orders","templated":"yes"}}}"#;
+    assert_eq!(ResourceRef::parse(text), Err(Resource::parse(&Json::from_str(text).unwrap()).unwrap_err()));
+}
+
+#[test]
+fn view_deeply_nested() {
+    let depth = 100_000;
+    let text = format!(r#"{{"nested":{}{}}}"#, "[".repeat(depth), "]".repeat(depth));
+    let view = ResourceRef::parse(&text).unwrap();
+    assert!(view.get_raw_state("nested").unwrap().starts_with("[["));
+
+    let text = format!(r#"{{"nested":{}}}"#, "[".repeat(depth));
+    assert!(ResourceRef::parse(&text).is_err());
+}
+
+#[test]
+fn view_duplicate_keys() {
+    let view = ResourceRef::parse(r#"{"total":1,"count":3,"total":2}"#).unwrap();
+    assert_eq!(view.get_state("total"), Some(&HalState::U64(2)));
+    assert_eq!(view.state_entries().map(|(key, _)| key).collect::<Vec<_>>(), vec!["count", "total"]);
+}
+
+#[test]
+fn view_template() {
+    let text = r#"{"_links":{"self":{"href":"/orders"}},"_templates":{"default":{"method":"POST","title":"Create"}},"total":1}"#;
+    let view = ResourceRef::parse(text).unwrap();
+    assert_eq!(view.get_template("default").unwrap().map(|template| template.get_title().map(String::from)),
+               Some(Some("Create".to_string())));
+    assert_eq!(view.get_template("missing"), Ok(None));
+    // `_templates` is state, as it is for a resource that reads plain Hal
+    assert_eq!(view.state_entries().map(|(key, _)| key).collect::<Vec<_>>(), vec!["_templates", "total"]);
+    assert_eq!(view.to_resource().unwrap().state_entries().map(|(key, _)| key).collect::<Vec<_>>(),
+               vec!["_templates", "total"]);
+
+    let view = ResourceRef::parse(r#"{"_templates":{"default":{"title":"Create"}}}"#).unwrap();
+    assert_eq!(view.get_template("default"),
+               Err(HalError::MissingField { path: "/_templates/default".to_string(), field: "method".to_string() }));
+}
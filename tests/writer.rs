@@ -0,0 +1,76 @@
+extern crate hal;
+extern crate rustc_serialize as serialize;
+
+use hal::link::Link;
+use hal::writer;
+use serialize::json::{self, ToJson};
+use std::io::{self, Write};
+
+mod common;
+
+use common::spec;
+
+#[test]
+fn to_writer() {
+    let hal = spec();
+    let mut out = Vec::new();
+    writer::to_writer(&mut out, &hal).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), hal.to_json().to_string());
+    assert_eq!(hal.to_string(), hal.to_json().to_string());
+}
+
+#[test]
+fn to_writer_ordered() {
+    let mut hal = spec();
+    hal.preserve_order(true).add_state("id", 123i64);
+
+    let mut out = Vec::new();
+    writer::to_writer(&mut out, &hal).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), hal.to_string());
+
+    let mut out = Vec::new();
+    writer::to_writer_pretty(&mut out, &hal).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format!("{:#}", hal));
+}
+
+#[test]
+fn to_writer_pretty() {
+    let hal = spec();
+    let mut out = Vec::new();
+    writer::to_writer_pretty(&mut out, &hal).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), hal.to_json().pretty().to_string());
+    assert_eq!(format!("{:#}", hal), hal.to_json().pretty().to_string());
+}
+
+#[test]
+fn encode_link() {
+    let mut link = Link::new("/orders");
+    link.templated(false).title("Orders").hreflang("en").media_type("application/hal+json");
+    assert_eq!(json::encode(&link).unwrap(), link.to_json().to_string());
+
+    let link = Link::new("/orders{?id}");
+    assert_eq!(json::encode(&link).unwrap(), r#"{"href":"/orders{?id}","templated":true}"#);
+}
+
+struct Full(usize);
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.0 < buf.len() {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"));
+        }
+        self.0 -= buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn to_writer_error() {
+    let e = writer::to_writer(Full(10), &spec()).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(e.to_string(), "disk full");
+}